@@ -15,6 +15,11 @@ from solana import publickey
 import yaml
 from yaml.loader import SafeLoader
 
+try:
+    import gadgets_sad
+except ImportError:
+    gadgets_sad = None
+
 
 class Node():
     """Base tree node"""
@@ -323,7 +328,39 @@ class Deserializer():
         return self.tree.deser(account, program, in_stream)
 
 
-def deserializer(file_name: Path) -> Deserializer:
-    """Load and parse the data deserialize declaration"""
+class RustDeserializer():
+    """RustDeserializer
+
+    Deserializes data stream based on data declaration
+    model with the Rust gadgets_sad module"""
+
+    def __init__(self, file_name: Path) -> None:
+        self._deseriaizer = gadgets_sad.Deseriaizer.from_file(str(file_name))
+
+    @ property
+    def name(self) -> str:
+        """Declaration name"""
+        return self._deseriaizer.name
+
+    def describe(self) -> None:
+        """Describe the declaration items"""
+        for item_type in self._deseriaizer.item_types:
+            print(f"Type {item_type}")
+
+    def deser(self, account: str, program: str, in_stream: BytesIO) -> dict:
+        """Deserialize the inbound bytes"""
+        result_dict = {
+            "account_key": account,
+            "account_program_key": program
+        }
+        result_dict.update(self._deseriaizer.deser(in_stream.read()))
+        return result_dict
+
+
+def deserializer(file_name: Path):
+    """Load and parse the data deserialize declaration, with the
+    Rust implementation when the gadgets_sad module is installed"""
+    if gadgets_sad is not None:
+        return RustDeserializer(file_name)
     with open(file_name) as f:
         return Deserializer(yaml.load(f, Loader=SafeLoader))
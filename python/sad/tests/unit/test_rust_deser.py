@@ -0,0 +1,47 @@
+import base64
+import io
+from pathlib import Path
+import pytest
+
+gadgets_sad = pytest.importorskip("gadgets_sad")
+
+from src.datadeser import RustDeserializer  # noqa: E402
+
+SCLI = "../../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml"
+PACC = "ASUAAAABAAAABAAAAEFLZXkVAAAATWludGVkIGtleSB2YWx1ZSBwYWlyAAAA"
+
+
+def test_load_layout_pass() -> None:
+    assert "spl_token_mint" in gadgets_sad.layout_names()
+    mint = gadgets_sad.Deseriaizer.from_layout("spl_token_mint")
+    assert mint.name == "spl_token_mint"
+    assert mint.deser(bytes(82))["supply"] == 0
+
+
+def test_load_fail() -> None:
+    with pytest.raises(ValueError):
+        gadgets_sad.Deseriaizer("foo: [")
+    with pytest.raises(ValueError):
+        gadgets_sad.Deseriaizer.from_layout("nope")
+    with pytest.raises(FileNotFoundError):
+        gadgets_sad.Deseriaizer.from_file("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml")
+
+
+def test_deserialize_simple_map_pass() -> None:
+    dd = RustDeserializer(Path(SCLI))
+    res = dd.deser("account", "program", io.BytesIO(base64.b64decode(PACC)))
+    assert res == {
+        "account_key": "account",
+        "account_program_key": "program",
+        "initialized": True,
+        "map_length": 37,
+        "map": {"AKey": "Minted key value pair"},
+    }
+
+
+def test_deserialize_short_data_fail() -> None:
+    mint = gadgets_sad.Deseriaizer.from_layout("spl_token_mint")
+    with pytest.raises(ValueError, match="Expected 4 bytes of data, found 3"):
+        mint.deser(bytes(3))
+    with pytest.raises(ValueError):
+        mint.to_json(bytes(81))
@@ -1,18 +1,106 @@
 ///! solana-gadgets common resuable modules
 // Includes
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::{fs::File, io};
-use yaml_rust::yaml::Yaml;
+use yaml_rust::yaml::{Hash, Yaml};
 use yaml_rust::YamlLoader;
 
+const YAML_INCLUDE: &str = "include";
+const YAML_TYPES: &str = "types";
+
 /// Loads a yaml file returning a YAML document
+///
+/// A document may carry an `include:` entry, a path or list of paths relative
+/// to the including file. The `types:` of included documents are merged into
+/// the including document's `types:`, with its own definitions taking precedence
 pub fn load_yaml_file(yaml_file: &str) -> Result<Vec<Yaml>, io::Error> {
+    load_yaml_with_includes(Path::new(yaml_file), &mut Vec::<PathBuf>::new())
+}
+
+fn load_yaml_with_includes(
+    yaml_file: &Path,
+    visiting: &mut Vec<PathBuf>,
+) -> Result<Vec<Yaml>, io::Error> {
     let mut file = File::open(yaml_file)?;
     let mut contents = String::new();
 
     file.read_to_string(&mut contents)?;
-    let docs = YamlLoader::load_from_str(&contents).unwrap();
-    Ok(docs)
+    let docs = YamlLoader::load_from_str(&contents)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let canonical = yaml_file.canonicalize()?;
+    if visiting.contains(&canonical) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Include cycle through {}", yaml_file.display()),
+        ));
+    }
+    visiting.push(canonical);
+    let base = yaml_file.parent().unwrap_or_else(|| Path::new("."));
+    let result = docs
+        .into_iter()
+        .map(|doc| resolve_includes(doc, base, visiting))
+        .collect();
+    visiting.pop();
+    result
+}
+
+/// Replaces a document's `include:` entry with the `types:` of
+/// the included documents
+fn resolve_includes(
+    doc: Yaml,
+    base: &Path,
+    visiting: &mut Vec<PathBuf>,
+) -> Result<Yaml, io::Error> {
+    let mut hmap = match doc {
+        Yaml::Hash(hmap) => hmap,
+        _ => return Ok(doc),
+    };
+    let include_key = Yaml::String(YAML_INCLUDE.to_string());
+    let includes = match hmap.remove(&include_key) {
+        Some(Yaml::String(s)) => vec![s],
+        Some(Yaml::Array(lst)) => lst
+            .iter()
+            .map(|i| {
+                i.as_str().map(|s| s.to_string()).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Expected include path")
+                })
+            })
+            .collect::<Result<Vec<String>, io::Error>>()?,
+        Some(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Expected include path or list of paths",
+            ))
+        }
+        None => return Ok(Yaml::Hash(hmap)),
+    };
+    let types_key = Yaml::String(YAML_TYPES.to_string());
+    let mut types = match hmap.remove(&types_key) {
+        Some(Yaml::Hash(t)) => t,
+        Some(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Expected types to be a HashMap",
+            ))
+        }
+        None => Hash::new(),
+    };
+    for include in includes {
+        for included in load_yaml_with_includes(&base.join(include), visiting)? {
+            if let Yaml::Hash(ihash) = included {
+                if let Some(Yaml::Hash(itypes)) = ihash.get(&types_key) {
+                    for (k, v) in itypes {
+                        if !types.contains_key(k) {
+                            types.insert(k.clone(), v.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    hmap.insert(types_key, Yaml::Hash(types));
+    Ok(Yaml::Hash(hmap))
 }
 
 #[cfg(test)]
@@ -22,6 +110,7 @@ mod tests {
     use std::env;
     const SCLI: &str = "../../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml";
     const NONEXIST: &str = "../../samples/yamldecls/nothere.yml";
+    const INCLUDER: &str = "../yaml_samps/include_main.yml";
 
     fn print_indent(indent: usize) {
         for _ in 0..indent {
@@ -63,4 +152,14 @@ mod tests {
         let y = load_yaml_file(NONEXIST);
         assert!(y.is_err());
     }
+    #[test]
+    fn load_yaml_file_include_pass() {
+        let result = load_yaml_file(INCLUDER).unwrap();
+        let doc = &result[0];
+        dump_node(doc, 0);
+        assert!(doc["include"].is_badvalue());
+        // Included definition merged, local definition kept
+        assert_eq!(doc["types"]["Person"]["type"].as_str(), Some("CStruct"));
+        assert_eq!(doc["types"]["Counter"]["type"].as_str(), Some("U64"));
+    }
 }
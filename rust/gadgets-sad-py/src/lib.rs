@@ -0,0 +1,154 @@
+//! @brief Python bindings of the gadgets-sad deserializer
+//! Exposes a Deseriaizer that loads a YAML declaration and decodes
+//! account data to Python dicts, lists and scalars
+
+use {
+    gadgets_common::load_yaml_file,
+    gadgets_sad::{library, Deseriaizer, SadTreeError},
+    pyo3::{
+        exceptions::PyValueError,
+        prelude::*,
+        types::{PyDict, PyList},
+    },
+    serde_json::Value,
+};
+
+/// Errors of the bindings, declaration and data errors are raised
+/// as a ValueError
+enum SadPyError {
+    Tree(SadTreeError),
+    Py(PyErr),
+}
+
+impl From<SadTreeError> for SadPyError {
+    fn from(err: SadTreeError) -> Self {
+        SadPyError::Tree(err)
+    }
+}
+
+impl From<PyErr> for SadPyError {
+    fn from(err: PyErr) -> Self {
+        SadPyError::Py(err)
+    }
+}
+
+impl From<std::io::Error> for SadPyError {
+    fn from(err: std::io::Error) -> Self {
+        SadPyError::Py(err.into())
+    }
+}
+
+impl From<SadPyError> for PyErr {
+    fn from(err: SadPyError) -> Self {
+        match err {
+            SadPyError::Tree(e) => PyValueError::new_err(e.to_string()),
+            SadPyError::Py(e) => e,
+        }
+    }
+}
+
+type SadPyResult<T> = Result<T, SadPyError>;
+
+/// Python object of a JSON value
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_py(py),
+            (_, Some(i)) => i.into_py(py),
+            _ => n.as_f64().unwrap().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(list) => PyList::new_bound(
+            py,
+            list.iter()
+                .map(|v| to_py(py, v))
+                .collect::<PyResult<Vec<PyObject>>>()?,
+        )
+        .into_py(py),
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (k, v) in map {
+                dict.set_item(k, to_py(py, v)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+/// Deserializer of account data described by a YAML declaration
+#[pyclass(name = "Deseriaizer", module = "gadgets_sad", unsendable)]
+struct PyDeseriaizer {
+    inner: Deseriaizer,
+}
+
+#[pymethods]
+impl PyDeseriaizer {
+    /// Parses the first declaration of YAML text
+    #[new]
+    fn new(declaration: &str) -> SadPyResult<Self> {
+        Ok(Self {
+            inner: Deseriaizer::from_yaml_str(declaration)?,
+        })
+    }
+
+    /// Parses the first declaration of a YAML file, with its includes
+    #[staticmethod]
+    fn from_file(file_name: &str) -> SadPyResult<Self> {
+        let docs = load_yaml_file(file_name)?;
+        let doc = docs.first().ok_or(SadTreeError::ExpectedDescriptor)?;
+        Ok(Self {
+            inner: Deseriaizer::try_new(doc)?,
+        })
+    }
+
+    /// Parses a bundled layout by name
+    #[staticmethod]
+    fn from_layout(name: &str) -> SadPyResult<Self> {
+        Ok(Self {
+            inner: Deseriaizer::from_layout(name)?,
+        })
+    }
+
+    /// Name of the declaration
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name().clone()
+    }
+
+    /// Top level item names, each suffixed with its type
+    #[getter]
+    fn item_types(&self) -> Vec<String> {
+        self.inner.schema().item_type_prefixes().clone()
+    }
+
+    /// Decodes data to a dict of the declaration's items
+    /// Data that does not match the declaration raises a ValueError
+    fn deser(&self, py: Python<'_>, data: &[u8]) -> SadPyResult<PyObject> {
+        Ok(to_py(py, &self.inner.deser_to_json(data)?)?)
+    }
+
+    /// Decodes data to JSON text
+    fn to_json(&self, data: &[u8]) -> SadPyResult<String> {
+        Ok(self.inner.deser_to_json(data)?.to_string())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Deseriaizer('{}')", self.inner.name())
+    }
+}
+
+/// Names of the bundled layouts
+#[pyfunction]
+fn layout_names() -> Vec<&'static str> {
+    library::layout_names().to_vec()
+}
+
+#[pymodule]
+#[pyo3(name = "gadgets_sad")]
+fn gadgets_sad_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDeseriaizer>()?;
+    m.add_function(wrap_pyfunction!(layout_names, m)?)?;
+    Ok(())
+}
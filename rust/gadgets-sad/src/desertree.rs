@@ -0,0 +1,3163 @@
+//! @brief Heart of deserialization
+//! Constructs for instantiating parse tree with constructs
+//! from YAML declaration and then walking said tree to deserialize
+//! input vector of bytes that come from a program owned account data
+
+use {
+    crate::{
+        errors::{SadTreeError, SadTreeResult},
+        library::layout,
+        registry::match_json_schema,
+        sadtypes::{
+            deser_value_for, fixed_size_of, from_json_value_for, from_scalar_value_for,
+            is_sadvalue_type, SadEncoding, SadValue,
+        },
+    },
+    downcast_rs::{impl_downcast, Downcast},
+    lazy_static::*,
+    serde::{
+        de::{self, DeserializeSeed},
+        ser::{self, SerializeMap},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    serde_json::{json, Value},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        cell::OnceCell,
+        collections::{HashMap, HashSet},
+        rc::{Rc, Weak},
+    },
+    strum::VariantNames,
+    yaml_rust::{
+        yaml::{Hash, Yaml},
+        YamlEmitter, YamlLoader,
+    },
+};
+/// Simple Node for tree membership
+trait Node: std::fmt::Debug + Downcast {
+    /// Clone of the inbound yaml sad 'type'
+    fn decl_type(&self) -> &String;
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()>;
+    /// Size in bytes of the data, None when it varies with the data
+    fn fixed_size(&self) -> Option<usize> {
+        None
+    }
+}
+impl_downcast!(Node);
+
+/// Size in bytes of a sequence of nodes, None if any varies
+fn fixed_size_of_all(nodes: &[Box<dyn Node>]) -> Option<usize> {
+    nodes.iter().map(|n| n.fixed_size()).sum()
+}
+
+/// Simple branch for tree membership
+trait NodeWithChildren: Node {
+    fn children(&self) -> &Vec<Box<dyn Node>>;
+}
+impl_downcast!(NodeWithChildren);
+
+const SAD_YAML_TYPE: &str = "type";
+const SAD_YAML_NAME: &str = "name";
+const SAD_YAML_DESCRIPTOR: &str = "descriptor";
+// const SAD_YAML_SIZE_TYPE: &str = "size_type";
+const SAD_YAML_CONTAINS: &str = "contains";
+const SAD_YAML_FIELDS: &str = "fields";
+const SAD_YAML_TYPES: &str = "types";
+const SAD_YAML_REF: &str = "$ref";
+const SAD_YAML_IMPORT: &str = "import";
+const SAD_YAML_INCLUDE: &str = "include";
+const SAD_YAML_MATCH: &str = "match";
+const SAD_YAML_ENCODING: &str = "encoding";
+const SAD_YAML_TAG: &str = "tag";
+const SAD_YAML_VARIANTS: &str = "variants";
+const SAD_YAML_VALUE: &str = "value";
+const SAD_YAML_LENGTH: &str = "length";
+const SAD_YAML_LENGTH_TYPE: &str = "length_type";
+const SAD_YAML_TERMINATOR: &str = "terminator";
+/// Enum discriminator types, the first is the default
+const SAD_ENUM_TAGS: &[&str] = &["U8", "U16", "U32", "U64"];
+/// Top level declaration keys that are not the descriptor
+const SAD_RESERVED_KEYS: &[&str] = &[
+    SAD_YAML_TYPES,
+    SAD_YAML_IMPORT,
+    SAD_YAML_INCLUDE,
+    SAD_YAML_MATCH,
+    SAD_YAML_ENCODING,
+];
+const SAD_NAMED_FIELD: &str = "NamedField";
+const SAD_REF: &str = "Ref";
+const SAD_ENUM: &str = "Enum";
+const SAD_TLV: &str = "Tlv";
+const SAD_VARIANT: &str = "Variant";
+const SAD_UNKNOWN_VARIANT: &str = "unknown_variant";
+
+/// Signature of the YAML to Node constructors
+type NodeParser = fn(&Yaml, &SadParseContext) -> SadTreeResult<Box<dyn Node>>;
+
+// Jump table for generalizing parse construction
+lazy_static! {
+    static ref SAD_TYPE_JSON: Vec<Yaml> =
+        YamlLoader::load_from_str(&format!("{}", SAD_YAML_TYPE)).unwrap();
+    static ref SAD_REF_JSON: Yaml = Yaml::String(SAD_YAML_REF.to_string());
+    static ref JUMP_TABLE: HashMap<String, NodeParser> = {
+        let mut jump_table = HashMap::<String, NodeParser>::new();
+        jump_table.insert("HashMap".to_string(), SadHashMap::from_yaml);
+        jump_table.insert("Vec".to_string(), SadVector::from_yaml);
+        jump_table.insert("Array".to_string(), SadArray::from_yaml);
+        jump_table.insert("Option".to_string(), SadOption::from_yaml);
+        jump_table.insert("Tuple".to_string(), SadTuple::from_yaml);
+        jump_table.insert("CStruct".to_string(), SadStructure::from_yaml);
+        jump_table.insert("NamedField".to_string(), SadNamedField::from_yaml);
+        jump_table.insert("PublicKey".to_string(), SadPublicKey::from_yaml);
+        jump_table.insert("Ref".to_string(), SadRef::from_yaml);
+        jump_table.insert("Enum".to_string(), SadEnum::from_yaml);
+        jump_table.insert("Tlv".to_string(), SadTlv::from_yaml);
+        jump_table.insert("other".to_string(), SadLeaf::from_yaml);
+        jump_table
+    };
+    static ref SAD_JUMP_OTHER: NodeParser = *JUMP_TABLE.get("other").unwrap();
+    static ref SAD_PUBKEY_CHILD: Vec<Yaml> =
+        YamlLoader::load_from_str(&format!("{}", "type: Vec\ncontains: \n    - type: U32"))
+            .unwrap();
+}
+
+/// Implements Node for low level scalar types
+#[derive(Debug)]
+pub struct SadLeaf {
+    sad_value_type: String,
+    encoding: SadEncoding,
+}
+
+impl SadLeaf {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        if is_sadvalue_type(in_str) {
+            Ok(Box::new(SadLeaf {
+                sad_value_type: String::from(in_str),
+                encoding: ctx.encoding,
+            }))
+        } else {
+            Err(SadTreeError::UnknownType(String::from(in_str)))
+        }
+    }
+}
+
+impl Node for SadLeaf {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        collection.push(self.encoding.deser_value_for(self.decl_type(), data)?);
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of(self.decl_type())
+    }
+}
+
+#[derive(Debug)]
+pub struct SadPublicKey {
+    sad_value_type: String,
+}
+
+impl SadPublicKey {
+    fn from_yaml(in_yaml: &Yaml, _ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        if is_sadvalue_type(in_str) {
+            Ok(Box::new(SadPublicKey {
+                sad_value_type: String::from(in_str),
+            }))
+        } else {
+            Err(SadTreeError::UnknownType(String::from(in_str)))
+        }
+    }
+}
+
+impl Node for SadPublicKey {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        collection.push(deser_value_for(self.decl_type(), data)?);
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of(self.decl_type())
+    }
+}
+
+/// Implements NodeWithChildren for SadStructure Named Fields
+#[derive(Debug)]
+pub struct SadNamedField {
+    sad_field_name: String,
+    sad_value_type: String,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadNamedField {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let desc = &in_yaml[SAD_YAML_DESCRIPTOR];
+        let in_name = desc[SAD_YAML_NAME]
+            .as_str()
+            .ok_or(SadTreeError::ExpectedFieldName)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let contains = &desc["contains"];
+        array.push(parse(contains, ctx)?);
+        Ok(Box::new(SadNamedField {
+            sad_field_name: String::from(in_name),
+            sad_value_type: String::from(SAD_NAMED_FIELD),
+            children: array,
+        }))
+    }
+
+    fn name(&self) -> &String {
+        &self.sad_field_name
+    }
+}
+
+impl Node for SadNamedField {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let mut coll = Vec::<SadValue>::new();
+        coll.push(SadValue::String(self.name().clone()));
+        for c in &self.children {
+            c.deser(data, &mut coll)?
+        }
+        collection.push(SadValue::NamedField(coll));
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of_all(&self.children)
+    }
+}
+impl NodeWithChildren for SadNamedField {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for HashMap
+#[derive(Debug)]
+pub struct SadHashMap {
+    sad_value_type: String,
+    encoding: SadEncoding,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadHashMap {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let fields = &in_yaml[SAD_YAML_FIELDS];
+        match fields {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadHashMap {
+                    sad_value_type: String::from(in_str),
+                    encoding: ctx.encoding,
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedHashMapFields),
+        }
+    }
+}
+impl Node for SadHashMap {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let mut coll = Vec::<Vec<SadValue>>::new();
+        let count = self.encoding.deser_length(data)?;
+        for _ in 0..count {
+            let mut spare = Vec::<SadValue>::new();
+            for c in &self.children {
+                c.deser(data, &mut spare)?;
+            }
+            coll.push(spare);
+        }
+        collection.push(SadValue::HashMap(coll));
+        Ok(())
+    }
+}
+
+impl NodeWithChildren for SadHashMap {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for Structure (i.e. Rust struct)
+#[derive(Debug)]
+pub struct SadStructure {
+    sad_value_type: String,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadStructure {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let fields = &in_yaml[SAD_YAML_FIELDS];
+
+        match fields {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadStructure {
+                    sad_value_type: String::from(in_str),
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedCStructFields),
+        }
+    }
+}
+impl Node for SadStructure {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let mut coll = Vec::<SadValue>::new();
+        for c in &self.children {
+            c.deser(data, &mut coll)?
+        }
+        collection.push(SadValue::CStruct(coll));
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of_all(&self.children)
+    }
+}
+
+impl NodeWithChildren for SadStructure {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for Vector (i.e. Rust Vec)
+#[derive(Debug)]
+pub struct SadVector {
+    sad_value_type: String,
+    encoding: SadEncoding,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadVector {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let contains = &in_yaml[SAD_YAML_CONTAINS];
+        match contains {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadVector {
+                    sad_value_type: String::from(in_str),
+                    encoding: ctx.encoding,
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedVecContains),
+        }
+    }
+}
+impl Node for SadVector {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        // let mut coll = Vec::<Vec<SadValue>>::new();
+        let count = self.encoding.deser_length(data)?;
+        let mut spare = Vec::<SadValue>::new();
+        for _ in 0..count {
+            for c in &self.children {
+                c.deser(data, &mut spare)?;
+            }
+        }
+        collection.push(SadValue::Vec(spare));
+        Ok(())
+    }
+}
+
+impl NodeWithChildren for SadVector {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for Array (i.e. Rust [T; N]) where
+/// the 'length:' is declared rather than prefixed to the data
+#[derive(Debug)]
+pub struct SadArray {
+    sad_value_type: String,
+    length: usize,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadArray {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let length = match &in_yaml[SAD_YAML_LENGTH] {
+            Yaml::Integer(i) if *i >= 0 => *i as usize,
+            _ => return Err(SadTreeError::ExpectedArrayLength),
+        };
+        let mut array = Vec::<Box<dyn Node>>::new();
+        match &in_yaml[SAD_YAML_CONTAINS] {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadArray {
+                    sad_value_type: String::from(in_str),
+                    length,
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedVecContains),
+        }
+    }
+}
+
+impl Node for SadArray {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let mut spare = Vec::<SadValue>::new();
+        for _ in 0..self.length {
+            for c in &self.children {
+                c.deser(data, &mut spare)?;
+            }
+        }
+        collection.push(SadValue::Vec(spare));
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of_all(&self.children).map(|s| s * self.length)
+    }
+}
+
+impl NodeWithChildren for SadArray {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for Option (i.e. Rust Option) where
+/// a leading U8 of 1 indicates the contained value follows
+#[derive(Debug)]
+pub struct SadOption {
+    sad_value_type: String,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadOption {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        match &in_yaml[SAD_YAML_CONTAINS] {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadOption {
+                    sad_value_type: String::from(in_str),
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedOptionContains),
+        }
+    }
+}
+
+impl Node for SadOption {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    /// Produces an Option that is empty for None
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let (flag, rest) = data
+            .split_first()
+            .ok_or(SadTreeError::InsufficientData(1, 0))?;
+        let is_some = match flag {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(SadTreeError::InvalidData(
+                    "Option".to_string(),
+                    format!("invalid tag {}", flag),
+                ))
+            }
+        };
+        *data = rest;
+        let mut spare = Vec::<SadValue>::new();
+        if is_some {
+            for c in &self.children {
+                c.deser(data, &mut spare)?;
+            }
+        }
+        collection.push(SadValue::Option(spare));
+        Ok(())
+    }
+}
+
+impl NodeWithChildren for SadOption {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements NodeWithChildren for Tuple (i.e. Rust tuple)
+#[derive(Debug)]
+pub struct SadTuple {
+    sad_value_type: String,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadTuple {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let fields = &in_yaml[SAD_YAML_FIELDS];
+        match fields {
+            Yaml::Array(lst) => {
+                for hl in lst {
+                    array.push(parse(hl, ctx)?)
+                }
+                Ok(Box::new(SadTuple {
+                    sad_value_type: String::from(in_str),
+                    children: array,
+                }))
+            }
+            _ => Err(SadTreeError::ExpectedTupleFields),
+        }
+    }
+}
+impl Node for SadTuple {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let mut spare = Vec::<SadValue>::new();
+        for c in &self.children {
+            c.deser(data, &mut spare)?
+        }
+        collection.push(SadValue::Tuple(spare));
+        Ok(())
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        fixed_size_of_all(&self.children)
+    }
+}
+
+impl NodeWithChildren for SadTuple {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Variant of an Enum with its discriminator value and fields
+#[derive(Debug)]
+pub struct SadVariant {
+    sad_variant_name: String,
+    discriminator: u64,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl SadVariant {
+    fn name(&self) -> &String {
+        &self.sad_variant_name
+    }
+
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+/// Implements Node for Enum (i.e. Rust enum) where a leading
+/// discriminator 'tag:' selects which variant's fields follow
+#[derive(Debug)]
+pub struct SadEnum {
+    sad_value_type: String,
+    sad_tag_type: String,
+    variants: Vec<SadVariant>,
+}
+
+/// Integer type of a 'tag:' or 'length_type:'
+fn int_type_for(in_yaml: &Yaml) -> Option<&str> {
+    match in_yaml {
+        Yaml::String(t) if SAD_ENUM_TAGS.contains(&t.as_str()) => Some(t.as_str()),
+        _ => None,
+    }
+}
+
+/// Value of an integer read for a 'tag:' or 'length_type:'
+fn int_value_of(value: SadValue) -> u64 {
+    match value {
+        SadValue::U8(t) => t as u64,
+        SadValue::U16(t) => t as u64,
+        SadValue::U32(t) => t as u64,
+        SadValue::U64(t) => t,
+        _ => unreachable!(),
+    }
+}
+
+/// Size in bytes of the integer types in SAD_ENUM_TAGS
+fn int_size_of(int_type: &str) -> usize {
+    match int_type {
+        "U8" => 1,
+        "U16" => 2,
+        "U32" => 4,
+        "U64" => 8,
+        _ => unreachable!(),
+    }
+}
+
+/// Parses the 'variants:' of an Enum or the entries of a Tlv, where a
+/// variant's discriminator defaults to its position
+fn variants_from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Vec<SadVariant>> {
+    let lst = in_yaml[SAD_YAML_VARIANTS]
+        .as_vec()
+        .ok_or(SadTreeError::ExpectedEnumVariants)?;
+    let mut variants = Vec::<SadVariant>::new();
+    for (index, v) in lst.iter().enumerate() {
+        let name = v[SAD_YAML_NAME]
+            .as_str()
+            .ok_or(SadTreeError::ExpectedEnumVariants)?;
+        let discriminator = match &v[SAD_YAML_VALUE] {
+            Yaml::BadValue => index as u64,
+            Yaml::Integer(i) if *i >= 0 => *i as u64,
+            _ => return Err(SadTreeError::ExpectedEnumVariants),
+        };
+        let mut array = Vec::<Box<dyn Node>>::new();
+        match &v[SAD_YAML_FIELDS] {
+            Yaml::BadValue => {}
+            Yaml::Array(fields) => {
+                for hl in fields {
+                    array.push(parse(hl, ctx)?)
+                }
+            }
+            _ => return Err(SadTreeError::ExpectedEnumVariants),
+        }
+        variants.push(SadVariant {
+            sad_variant_name: String::from(name),
+            discriminator,
+            children: array,
+        })
+    }
+    Ok(variants)
+}
+
+impl SadEnum {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        // The encoding determines the tag when none is declared
+        let tag = match &in_yaml[SAD_YAML_TAG] {
+            Yaml::BadValue => ctx.encoding.default_tag(),
+            other => int_type_for(other)
+                .ok_or_else(|| SadTreeError::ExpectedEnumTag(format!("{:?}", other)))?,
+        };
+        Ok(Box::new(SadEnum {
+            sad_value_type: String::from(in_str),
+            sad_tag_type: String::from(tag),
+            variants: variants_from_yaml(in_yaml, ctx)?,
+        }))
+    }
+
+    fn variants(&self) -> &Vec<SadVariant> {
+        &self.variants
+    }
+}
+
+impl Node for SadEnum {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    /// Produces an Enum of the variant name followed by its field values,
+    /// or of only the discriminator when it matches no variant
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let discriminator = int_value_of(deser_value_for(&self.sad_tag_type, data)?);
+        let mut coll = Vec::<SadValue>::new();
+        match self
+            .variants
+            .iter()
+            .find(|v| v.discriminator == discriminator)
+        {
+            Some(variant) => {
+                coll.push(SadValue::String(variant.name().clone()));
+                for c in variant.children() {
+                    c.deser(data, &mut coll)?
+                }
+            }
+            None => coll.push(SadValue::U64(discriminator)),
+        }
+        collection.push(SadValue::Enum(coll));
+        Ok(())
+    }
+}
+
+/// Implements Node for Tlv, a sequence of (type, length, value) records
+/// that runs to the end of the data, or to a 'terminator:' type. Each
+/// record's value is deserialized with the variant its type selects
+#[derive(Debug)]
+pub struct SadTlv {
+    sad_value_type: String,
+    sad_tag_type: String,
+    sad_length_type: String,
+    terminator: Option<u64>,
+    variants: Vec<SadVariant>,
+}
+
+impl SadTlv {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_str = type_name(in_yaml)?;
+        // Record type and length default to U16
+        let int_type = |key: &str| match &in_yaml[key] {
+            Yaml::BadValue => Ok("U16"),
+            other => int_type_for(other)
+                .ok_or_else(|| SadTreeError::ExpectedTlvType(format!("{:?}", other))),
+        };
+        let terminator = match &in_yaml[SAD_YAML_TERMINATOR] {
+            Yaml::BadValue => None,
+            Yaml::Integer(i) if *i >= 0 => Some(*i as u64),
+            other => return Err(SadTreeError::ExpectedTlvType(format!("{:?}", other))),
+        };
+        Ok(Box::new(SadTlv {
+            sad_value_type: String::from(in_str),
+            sad_tag_type: String::from(int_type(SAD_YAML_TAG)?),
+            sad_length_type: String::from(int_type(SAD_YAML_LENGTH_TYPE)?),
+            terminator,
+            variants: variants_from_yaml(in_yaml, ctx)?,
+        }))
+    }
+
+    fn variants(&self) -> &Vec<SadVariant> {
+        &self.variants
+    }
+}
+
+impl Node for SadTlv {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    /// Produces a Tlv of Enums, one per record, as for Enum. A value is
+    /// deserialized from its record only, leaving any unread bytes behind.
+    /// A record cut short by the end of the data is an error
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let tag_size = int_size_of(&self.sad_tag_type);
+        let header_size = tag_size + int_size_of(&self.sad_length_type);
+        let mut coll = Vec::<SadValue>::new();
+        while !data.is_empty() {
+            if data.len() < tag_size {
+                return Err(SadTreeError::InsufficientData(header_size, data.len()));
+            }
+            let record_type = int_value_of(deser_value_for(&self.sad_tag_type, data)?);
+            if self.terminator == Some(record_type) {
+                break;
+            }
+            if data.len() < header_size - tag_size {
+                return Err(SadTreeError::InsufficientData(
+                    header_size,
+                    data.len() + tag_size,
+                ));
+            }
+            let length = int_value_of(deser_value_for(&self.sad_length_type, data)?) as usize;
+            if data.len() < length {
+                return Err(SadTreeError::InsufficientData(length, data.len()));
+            }
+            let (mut value, rest) = data.split_at(length);
+            *data = rest;
+            let mut record = Vec::<SadValue>::new();
+            match self
+                .variants
+                .iter()
+                .find(|v| v.discriminator == record_type)
+            {
+                Some(variant) => {
+                    record.push(SadValue::String(variant.name().clone()));
+                    for c in variant.children() {
+                        c.deser(&mut value, &mut record)?
+                    }
+                }
+                None => record.push(SadValue::U64(record_type)),
+            }
+            coll.push(SadValue::Enum(record))
+        }
+        collection.push(SadValue::Tlv(coll));
+        Ok(())
+    }
+}
+
+/// Implements Node for references to named definitions in
+/// the declaration 'types:' section
+#[derive(Debug)]
+pub struct SadRef {
+    sad_value_type: String,
+    sad_ref_name: String,
+    target: Weak<OnceCell<Box<dyn Node>>>,
+}
+
+impl SadRef {
+    fn from_yaml(in_yaml: &Yaml, ctx: &SadParseContext) -> SadTreeResult<Box<dyn Node>> {
+        let in_name = match in_yaml[SAD_YAML_REF].as_str() {
+            Some(n) => n,
+            None => return Err(SadTreeError::ExpectedRefName),
+        };
+        match ctx.types.get(in_name) {
+            Some(cell) => Ok(Box::new(SadRef {
+                sad_value_type: String::from(SAD_REF),
+                sad_ref_name: String::from(in_name),
+                target: Rc::downgrade(cell),
+            })),
+            None => Err(SadTreeError::UnknownTypeReference(String::from(in_name))),
+        }
+    }
+
+    fn name(&self) -> &String {
+        &self.sad_ref_name
+    }
+}
+
+impl Node for SadRef {
+    fn decl_type(&self) -> &String {
+        &self.sad_value_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        let cell = self.target.upgrade().unwrap();
+        cell.get().unwrap().deser(data, collection)
+    }
+
+    fn fixed_size(&self) -> Option<usize> {
+        let cell = self.target.upgrade().unwrap();
+        cell.get().unwrap().fixed_size()
+    }
+}
+
+/// Declaration wide state used while constructing the parse tree
+#[derive(Debug, Default)]
+struct SadParseContext {
+    types: HashMap<String, Rc<OnceCell<Box<dyn Node>>>>,
+    encoding: SadEncoding,
+}
+
+impl SadParseContext {
+    /// Builds the named type definitions from the 'types:' section.
+    /// Every name is registered before any definition is parsed so that
+    /// definitions may reference each other, and themselves, in any order
+    fn from_types(defs: &Hash, encoding: SadEncoding) -> SadTreeResult<Self> {
+        let mut ctx = SadParseContext {
+            encoding,
+            ..SadParseContext::default()
+        };
+        let mut names = Vec::<(String, &Yaml)>::new();
+        for (k, v) in defs {
+            let name = k.as_str().ok_or(SadTreeError::ExpectedTypesHashMap)?;
+            ctx.types
+                .insert(name.to_string(), Rc::new(OnceCell::<Box<dyn Node>>::new()));
+            names.push((name.to_string(), v));
+        }
+        for (name, def) in &names {
+            check_type_cycles(name, def, defs, &mut Vec::<String>::new())?;
+        }
+        for (name, def) in names {
+            let node = parse(def, &ctx)?;
+            ctx.types.get(&name).unwrap().set(node).unwrap();
+        }
+        Ok(ctx)
+    }
+
+    /// Resolved named definitions
+    fn definitions(&self) -> HashMap<&String, &dyn Node> {
+        self.types
+            .iter()
+            .map(|(k, v)| (k, v.get().unwrap().as_ref()))
+            .collect()
+    }
+}
+
+/// Gathers the 'types:' of a declaration together with those of the
+/// bundled layouts it imports. Local definitions take precedence
+fn collect_types(hmap: &Hash, importing: &mut Vec<String>) -> SadTreeResult<Hash> {
+    let mut types = match hmap.get(&Yaml::String(SAD_YAML_TYPES.to_string())) {
+        Some(Yaml::Hash(t)) => t.clone(),
+        Some(_) => return Err(SadTreeError::ExpectedTypesHashMap),
+        None => Hash::new(),
+    };
+    let imports = match hmap.get(&Yaml::String(SAD_YAML_IMPORT.to_string())) {
+        Some(Yaml::String(s)) => vec![s.clone()],
+        Some(Yaml::Array(lst)) => lst
+            .iter()
+            .map(|i| {
+                i.as_str()
+                    .map(String::from)
+                    .ok_or(SadTreeError::ExpectedImportName)
+            })
+            .collect::<SadTreeResult<Vec<String>>>()?,
+        Some(_) => return Err(SadTreeError::ExpectedImportName),
+        None => Vec::<String>::new(),
+    };
+    for name in imports {
+        if importing.contains(&name) {
+            return Err(SadTreeError::CyclicImport(name));
+        }
+        let docs = layout(&name).ok_or_else(|| SadTreeError::UnknownImport(name.clone()))?;
+        let lhash = docs[0].as_hash().ok_or(SadTreeError::ExpectedHashMap)?;
+        importing.push(name);
+        for (k, v) in collect_types(lhash, importing)? {
+            if !types.contains_key(&k) {
+                types.insert(k, v);
+            }
+        }
+        importing.pop();
+    }
+    Ok(types)
+}
+
+/// Walks the parts of a definition that are always deserialized
+/// (i.e. not guarded by a length, count, Option, Enum variant or
+/// Tlv record) and fails if a reference leads back to a definition
+/// already on the path, as that would never terminate
+fn check_type_cycles(
+    name: &str,
+    def: &Yaml,
+    defs: &Hash,
+    path: &mut Vec<String>,
+) -> SadTreeResult<()> {
+    if path.iter().any(|p| p == name) {
+        return Err(SadTreeError::CyclicTypeReference(name.to_string()));
+    }
+    path.push(name.to_string());
+    let mut pending = vec![def];
+    let mut seen = HashSet::<String>::new();
+    while let Some(node) = pending.pop() {
+        if let Some(target) = node[SAD_YAML_REF].as_str() {
+            if seen.insert(target.to_string()) {
+                match defs.get(&Yaml::String(target.to_string())) {
+                    Some(tdef) => check_type_cycles(target, tdef, defs, path)?,
+                    None => return Err(SadTreeError::UnknownTypeReference(target.to_string())),
+                }
+            }
+            continue;
+        }
+        match node[SAD_YAML_TYPE].as_str() {
+            Some("CStruct") | Some("Tuple") => {
+                if let Some(fields) = node[SAD_YAML_FIELDS].as_vec() {
+                    pending.extend(fields.iter())
+                }
+            }
+            Some("Array") => {
+                if let Some(contains) = node[SAD_YAML_CONTAINS].as_vec() {
+                    pending.extend(contains.iter())
+                }
+            }
+            Some("NamedField") => pending.push(&node[SAD_YAML_DESCRIPTOR][SAD_YAML_CONTAINS]),
+            _ => {}
+        }
+    }
+    path.pop();
+    Ok(())
+}
+
+/// Implements NodeWithChildren for SadTree which holds
+/// the YAML parse tree for deserialization
+#[derive(Debug)]
+pub struct SadTree {
+    yaml_decl_type: String,
+    name: String,
+    varnames: Vec<String>,
+    children: Vec<Box<dyn Node>>,
+    context: SadParseContext,
+}
+
+impl SadTree {
+    pub fn new(in_yaml: &Yaml) -> SadTreeResult<Self> {
+        let mut array = Vec::<Box<dyn Node>>::new();
+        let mut vars = Vec::<String>::new();
+        match &*in_yaml {
+            Yaml::Hash(ref hmap) => {
+                let types = collect_types(hmap, &mut Vec::<String>::new())?;
+                let encoding = match hmap.get(&Yaml::String(SAD_YAML_ENCODING.to_string())) {
+                    None => SadEncoding::default(),
+                    Some(e) => e
+                        .as_str()
+                        .and_then(SadEncoding::from_name)
+                        .ok_or_else(|| SadTreeError::UnknownEncoding(format!("{:?}", e)))?,
+                };
+                let ctx = SadParseContext::from_types(&types, encoding)?;
+                let (key, value) = hmap
+                    .iter()
+                    .find(|(k, _)| !SAD_RESERVED_KEYS.contains(&k.as_str().unwrap_or_default()))
+                    .ok_or(SadTreeError::ExpectedDescriptor)?;
+                match value {
+                    Yaml::Array(hlobjects) => {
+                        for hl in hlobjects {
+                            let (varname, h1_value) = hl
+                                .as_hash()
+                                .and_then(|h| h.front())
+                                .ok_or(SadTreeError::ExpectedHashMap)?;
+                            let varname =
+                                varname.as_str().ok_or(SadTreeError::ExpectedDescriptor)?;
+                            vars.push(varname.to_string());
+                            array.push(parse(h1_value, &ctx)?);
+                        }
+                    }
+                    // A descriptor may be a reference to a CStruct definition
+                    // whose named fields become the top level items
+                    Yaml::Hash(_) if value[SAD_YAML_REF].as_str().is_some() => {
+                        let ref_name = value[SAD_YAML_REF].as_str().unwrap();
+                        let def =
+                            types
+                                .get(&Yaml::String(ref_name.to_string()))
+                                .ok_or_else(|| {
+                                    SadTreeError::UnknownTypeReference(ref_name.to_string())
+                                })?;
+                        if def[SAD_YAML_TYPE].as_str() != Some("CStruct") {
+                            return Err(SadTreeError::ExpectedCStructFields);
+                        }
+                        let fields = def[SAD_YAML_FIELDS]
+                            .as_vec()
+                            .ok_or(SadTreeError::ExpectedCStructFields)?;
+                        for field in fields {
+                            let desc = &field[SAD_YAML_DESCRIPTOR];
+                            let varname = desc[SAD_YAML_NAME]
+                                .as_str()
+                                .ok_or(SadTreeError::ExpectedCStructFields)?;
+                            vars.push(varname.to_string());
+                            array.push(parse(&desc[SAD_YAML_CONTAINS], &ctx)?);
+                        }
+                    }
+                    _ => return Err(SadTreeError::ExpectedArray),
+                }
+                Ok(Self {
+                    yaml_decl_type: String::from("tree"),
+                    name: key
+                        .as_str()
+                        .ok_or(SadTreeError::ExpectedDescriptor)?
+                        .to_string(),
+                    varnames: vars,
+                    children: array,
+                    context: ctx,
+                })
+            }
+            _ => Err(SadTreeError::ExpectedHashMap),
+        }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+}
+
+impl NodeWithChildren for SadTree {
+    fn children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+}
+
+impl Node for SadTree {
+    fn decl_type(&self) -> &String {
+        &self.yaml_decl_type
+    }
+
+    fn deser(&self, data: &mut &[u8], collection: &mut Vec<SadValue>) -> SadTreeResult<()> {
+        for c in &self.children {
+            c.deser(data, collection)?
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+/// SadSchemaElement describes the concrete element(s) (e.g. Scalar vs. Compound)
+/// and will contain nested elements if compound
+pub struct SadSchemaElement {
+    schema_type: String,
+    schema_ancillary_type: Option<String>,
+    scalar: bool,
+    items: Option<Vec<SadSchemaElement>>,
+    /// Element count of an Array
+    length: Option<usize>,
+}
+
+/// Schema of the named definitions from the declaration 'types:' section
+pub type SadSchemaTypes = HashMap<String, SadSchemaElement>;
+
+impl SadSchemaElement {
+    fn new(schema_type_name: &String) -> Self {
+        Self {
+            schema_type: schema_type_name.clone(),
+            ..SadSchemaElement::default()
+        }
+    }
+    fn ancillary_type(mut self, atype: &String) -> Self {
+        self.schema_ancillary_type = Some(atype.clone());
+        self
+    }
+
+    fn scalar(mut self, scal: bool) -> Self {
+        self.scalar = scal;
+        self
+    }
+    fn items(mut self, elements: Vec<SadSchemaElement>) -> Self {
+        self.items = Some(elements);
+        self
+    }
+    fn length(mut self, count: usize) -> Self {
+        self.length = Some(count);
+        self
+    }
+    pub fn is_scalar(&self) -> bool {
+        self.scalar
+    }
+
+    /// Declared type name, 'Ref' for references
+    pub fn get_type(&self) -> &String {
+        &self.schema_type
+    }
+
+    /// Field name of a CStruct field, definition name of a reference
+    /// or variant name of an Enum variant
+    pub fn get_ancillary_type(&self) -> Option<&String> {
+        self.schema_ancillary_type.as_ref()
+    }
+
+    /// Contained elements, empty for scalars
+    pub fn get_items(&self) -> &[SadSchemaElement] {
+        self.items.as_deref().unwrap_or_default()
+    }
+
+    /// Value of the element from its JSON, as SadElementValue serializes it
+    fn sad_from_json(&self, value: &Value, types: &SadSchemaTypes) -> SadTreeResult<SadValue> {
+        let items = self.items.as_deref().unwrap_or_default();
+        let mismatch =
+            || SadTreeError::UnexpectedValue(self.schema_type.clone(), value.to_string());
+        let of_each = |element: &SadSchemaElement, list: &[Value]| {
+            list.iter()
+                .map(|v| element.sad_from_json(v, types))
+                .collect::<SadTreeResult<Vec<SadValue>>>()
+        };
+        Ok(match (self.schema_type.as_str(), value) {
+            (SAD_REF, _) => {
+                let name = self.schema_ancillary_type.as_ref().unwrap();
+                return types.get(name).unwrap().sad_from_json(value, types);
+            }
+            (SAD_ENUM, _) => SadValue::Enum(self.variant_from_json(value, types)?),
+            (SAD_TLV, Value::Array(records)) => SadValue::Tlv(
+                records
+                    .iter()
+                    .map(|r| self.variant_from_json(r, types).map(SadValue::Enum))
+                    .collect::<SadTreeResult<Vec<SadValue>>>()?,
+            ),
+            ("Vec" | "Array", Value::Array(list)) => SadValue::Vec(of_each(&items[0], list)?),
+            ("Option", Value::Null) => SadValue::Option(vec![]),
+            ("Option", _) => SadValue::Option(vec![items[0].sad_from_json(value, types)?]),
+            ("Tuple", Value::Array(list)) if list.len() == items.len() => SadValue::Tuple(
+                items
+                    .iter()
+                    .zip(list)
+                    .map(|(element, v)| element.sad_from_json(v, types))
+                    .collect::<SadTreeResult<Vec<SadValue>>>()?,
+            ),
+            ("HashMap", Value::Object(entries)) => {
+                let mut pairs = Vec::<Vec<SadValue>>::new();
+                for (k, v) in entries {
+                    // Keys other than Strings are the JSON text of the key
+                    let key = if items[0].is_scalar() && items[0].schema_type == "String" {
+                        SadValue::String(k.clone())
+                    } else {
+                        let key_json = serde_json::from_str::<Value>(k).map_err(|_| mismatch())?;
+                        items[0].sad_from_json(&key_json, types)?
+                    };
+                    pairs.push(vec![key, items[1].sad_from_json(v, types)?]);
+                }
+                SadValue::HashMap(pairs)
+            }
+            ("CStruct", Value::Object(_)) => {
+                SadValue::CStruct(SadSchemaElement::fields_from_json(items, value, types)?)
+            }
+            (SAD_NAMED_FIELD, _) => SadValue::NamedField(vec![
+                SadValue::String(self.schema_ancillary_type.clone().unwrap()),
+                items[0].sad_from_json(value, types)?,
+            ]),
+            _ if self.is_scalar() => {
+                from_json_value_for(&self.schema_type, value).ok_or_else(mismatch)?
+            }
+            _ => return Err(mismatch()),
+        })
+    }
+
+    /// Enum values from the JSON of a variant, as rendered by SadElementValue
+    fn variant_from_json(
+        &self,
+        value: &Value,
+        types: &SadSchemaTypes,
+    ) -> SadTreeResult<Vec<SadValue>> {
+        let variant_named = |name: &String| {
+            self.items
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|v| v.schema_ancillary_type.as_ref() == Some(name))
+        };
+        let mismatch =
+            || SadTreeError::UnexpectedValue(self.schema_type.clone(), value.to_string());
+        match value {
+            Value::String(name) => match variant_named(name) {
+                Some(variant) if variant.items.as_ref().unwrap().is_empty() => {
+                    Ok(vec![SadValue::String(name.clone())])
+                }
+                _ => Err(mismatch()),
+            },
+            Value::Object(map) if map.len() == 1 => {
+                let (name, fields) = map.iter().next().unwrap();
+                match variant_named(name) {
+                    Some(variant) => {
+                        let mut values = vec![SadValue::String(name.clone())];
+                        values.extend(SadSchemaElement::fields_from_json(
+                            variant.items.as_ref().unwrap(),
+                            fields,
+                            types,
+                        )?);
+                        Ok(values)
+                    }
+                    None if name == SAD_UNKNOWN_VARIANT => {
+                        Ok(vec![SadValue::U64(fields.as_u64().ok_or_else(mismatch)?)])
+                    }
+                    None => Err(mismatch()),
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// Values of CStruct or Enum variant fields from their JSON, a map
+    /// when all are named, else a list
+    fn fields_from_json(
+        fields: &[SadSchemaElement],
+        value: &Value,
+        types: &SadSchemaTypes,
+    ) -> SadTreeResult<Vec<SadValue>> {
+        if fields.iter().all(|f| f.schema_type == SAD_NAMED_FIELD) {
+            fields
+                .iter()
+                .map(|f| {
+                    let name = f.schema_ancillary_type.as_ref().unwrap();
+                    match value.get(name) {
+                        Some(v) => f.sad_from_json(v, types),
+                        None => Err(SadTreeError::MissingValue(name.clone())),
+                    }
+                })
+                .collect()
+        } else {
+            match value.as_array() {
+                Some(list) if list.len() == fields.len() => fields
+                    .iter()
+                    .zip(list)
+                    .map(|(f, v)| f.sad_from_json(v, types))
+                    .collect(),
+                _ => Err(SadTreeError::UnexpectedValue(
+                    String::from("fields"),
+                    value.to_string(),
+                )),
+            }
+        }
+    }
+}
+
+/// A deserialized value with the schema element it was deserialized
+/// with, which shapes how it serializes
+struct SadElementValue<'a> {
+    element: &'a SadSchemaElement,
+    value: &'a SadValue,
+    types: &'a SadSchemaTypes,
+}
+
+impl<'a> SadElementValue<'a> {
+    fn of(&self, element: &'a SadSchemaElement, value: &'a SadValue) -> Self {
+        SadElementValue {
+            element,
+            value,
+            types: self.types,
+        }
+    }
+
+    /// HashMap key as serialized, keys other than Strings are the JSON
+    /// text of the key
+    fn map_key(
+        &self,
+        element: &'a SadSchemaElement,
+        key: &'a SadValue,
+    ) -> serde_json::Result<String> {
+        if element.is_scalar() && element.schema_type == "String" {
+            Ok(from_scalar_value_for(key))
+        } else {
+            serde_json::to_string(&self.of(element, key))
+        }
+    }
+
+    /// Public keys of the value with the path of the field holding each,
+    /// as in the value's JSON
+    fn public_keys(&self, path: &str, keys: &mut Vec<(String, Pubkey)>) {
+        let items = self.element.items.as_deref().unwrap_or_default();
+        match (self.element.schema_type.as_str(), self.value) {
+            (SAD_REF, _) => {
+                let name = self.element.schema_ancillary_type.as_ref().unwrap();
+                self.of(self.types.get(name).unwrap(), self.value)
+                    .public_keys(path, keys)
+            }
+            (SAD_ENUM | SAD_TLV, SadValue::Enum(values)) => {
+                if let Some(SadValue::String(name)) = values.first() {
+                    if let Some(variant) = items
+                        .iter()
+                        .find(|v| v.schema_ancillary_type.as_ref() == Some(name))
+                    {
+                        self.field_public_keys(
+                            variant.items.as_deref().unwrap_or_default(),
+                            &values[1..],
+                            &field_path(path, name),
+                            keys,
+                        )
+                    }
+                }
+            }
+            (SAD_TLV, SadValue::Tlv(records)) => {
+                for (i, record) in records.iter().enumerate() {
+                    self.of(self.element, record)
+                        .public_keys(&format!("{}[{}]", path, i), keys)
+                }
+            }
+            ("Vec" | "Array", SadValue::Vec(v)) => {
+                for (i, e) in v.iter().enumerate() {
+                    self.of(&items[0], e)
+                        .public_keys(&format!("{}[{}]", path, i), keys)
+                }
+            }
+            ("Option", SadValue::Option(v)) => {
+                if let Some(e) = v.first() {
+                    self.of(&items[0], e).public_keys(path, keys)
+                }
+            }
+            ("Tuple", SadValue::Tuple(v)) => {
+                for (i, (item, e)) in items.iter().zip(v.iter()).enumerate() {
+                    self.of(item, e)
+                        .public_keys(&format!("{}[{}]", path, i), keys)
+                }
+            }
+            ("HashMap", SadValue::HashMap(v)) => {
+                for kv in v {
+                    if let Ok(key) = self.map_key(&items[0], &kv[0]) {
+                        let entry_path = field_path(path, &key);
+                        self.of(&items[0], &kv[0]).public_keys(&entry_path, keys);
+                        self.of(&items[1], &kv[1]).public_keys(&entry_path, keys)
+                    }
+                }
+            }
+            ("CStruct", SadValue::CStruct(nfs)) => self.field_public_keys(items, nfs, path, keys),
+            (SAD_NAMED_FIELD, SadValue::NamedField(f)) => {
+                self.of(&items[0], &f[1]).public_keys(path, keys)
+            }
+            (_, SadValue::PublicKey(k)) => keys.push((path.to_string(), *k)),
+            _ => {}
+        }
+    }
+
+    /// Public keys of CStruct or Enum variant fields, named by field when
+    /// all are named, else by position
+    fn field_public_keys(
+        &self,
+        fields: &'a [SadSchemaElement],
+        values: &'a [SadValue],
+        path: &str,
+        keys: &mut Vec<(String, Pubkey)>,
+    ) {
+        let named = fields.iter().all(|f| f.schema_type == SAD_NAMED_FIELD);
+        for (i, (field, value)) in fields.iter().zip(values.iter()).enumerate() {
+            let value_path = if named {
+                field_path(path, field.schema_ancillary_type.as_ref().unwrap())
+            } else {
+                format!("{}[{}]", path, i)
+            };
+            self.of(field, value).public_keys(&value_path, keys)
+        }
+    }
+
+    fn mismatch<E: ser::Error>(&self) -> E {
+        E::custom(format!(
+            "Expected {} value, found {:?}",
+            self.element.schema_type, self.value
+        ))
+    }
+
+    /// Enums serialize as the variant name when it has no fields, otherwise
+    /// as a map of the variant name to its fields
+    fn serialize_variant<S: Serializer>(
+        &self,
+        values: &'a [SadValue],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let name = match values.first() {
+            Some(SadValue::String(name)) => name,
+            Some(SadValue::U64(discriminator)) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(SAD_UNKNOWN_VARIANT, discriminator)?;
+                return map.end();
+            }
+            _ => return Err(self.mismatch()),
+        };
+        let variant = self
+            .element
+            .items
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|v| v.schema_ancillary_type.as_ref() == Some(name))
+            .ok_or_else(|| self.mismatch())?;
+        let fields = variant.items.as_ref().unwrap();
+        if fields.is_empty() {
+            return serializer.serialize_str(name);
+        }
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(
+            name,
+            &SadFieldValues {
+                fields,
+                values: &values[1..],
+                types: self.types,
+            },
+        )?;
+        map.end()
+    }
+}
+
+impl Serialize for SadElementValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let element = self.element;
+        let items = element.items.as_deref().unwrap_or_default();
+        match (element.schema_type.as_str(), self.value) {
+            // References serialize as the named definition they resolve to
+            (SAD_REF, _) => {
+                let name = element.schema_ancillary_type.as_ref().unwrap();
+                self.of(self.types.get(name).unwrap(), self.value)
+                    .serialize(serializer)
+            }
+            (SAD_ENUM | SAD_TLV, SadValue::Enum(values)) => {
+                self.serialize_variant(values, serializer)
+            }
+            // Tlvs serialize as a list of their records, each as an Enum
+            (SAD_TLV, SadValue::Tlv(records)) => {
+                serializer.collect_seq(records.iter().map(|r| self.of(element, r)))
+            }
+            ("Vec" | "Array", SadValue::Vec(v)) => {
+                serializer.collect_seq(v.iter().map(|e| self.of(&items[0], e)))
+            }
+            ("Option", SadValue::Option(v)) => match v.first() {
+                Some(e) => serializer.serialize_some(&self.of(&items[0], e)),
+                None => serializer.serialize_none(),
+            },
+            ("Tuple", SadValue::Tuple(v)) => {
+                serializer.collect_seq(items.iter().zip(v.iter()).map(|(item, e)| self.of(item, e)))
+            }
+            ("HashMap", SadValue::HashMap(v)) => {
+                let mut map = serializer.serialize_map(Some(v.len()))?;
+                for kv in v {
+                    let key = self
+                        .map_key(&items[0], &kv[0])
+                        .map_err(ser::Error::custom)?;
+                    map.serialize_entry(&key, &self.of(&items[1], &kv[1]))?;
+                }
+                map.end()
+            }
+            ("CStruct", SadValue::CStruct(nfs)) => SadFieldValues {
+                fields: items,
+                values: nfs,
+                types: self.types,
+            }
+            .serialize(serializer),
+            (SAD_NAMED_FIELD, SadValue::NamedField(f)) => {
+                self.of(&items[0], &f[1]).serialize(serializer)
+            }
+            _ if element.items.is_some() => Err(self.mismatch()),
+            (_, SadValue::String(item)) => serializer.serialize_str(item),
+            (_, SadValue::Bool(item)) => serializer.serialize_bool(*item),
+            (_, SadValue::U8(item)) => serializer.serialize_u8(*item),
+            (_, SadValue::U16(item)) => serializer.serialize_u16(*item),
+            (_, SadValue::U32(item)) => serializer.serialize_u32(*item),
+            (_, SadValue::U64(item)) => serializer.serialize_u64(*item),
+            (_, SadValue::U128(item)) => serializer.collect_str(item),
+            (_, SadValue::I8(item)) => serializer.serialize_i8(*item),
+            (_, SadValue::I16(item)) => serializer.serialize_i16(*item),
+            (_, SadValue::I32(item)) => serializer.serialize_i32(*item),
+            (_, SadValue::I64(item)) => serializer.serialize_i64(*item),
+            (_, SadValue::I128(item)) => serializer.collect_str(item),
+            (_, SadValue::F32(item)) => serializer.serialize_f32(*item),
+            (_, SadValue::F64(item)) => serializer.serialize_f64(*item),
+            (_, SadValue::PublicKey(item)) => serializer.collect_str(item),
+            _ => Err(self.mismatch()),
+        }
+    }
+}
+
+/// CStruct or Enum variant fields, serializing as a map when all
+/// are named, else as a list
+struct SadFieldValues<'a> {
+    fields: &'a [SadSchemaElement],
+    values: &'a [SadValue],
+    types: &'a SadSchemaTypes,
+}
+
+impl Serialize for SadFieldValues<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let values = self
+            .fields
+            .iter()
+            .zip(self.values.iter())
+            .map(|(f, v)| SadElementValue {
+                element: f,
+                value: v,
+                types: self.types,
+            });
+        if self.fields.iter().all(|f| f.schema_type == SAD_NAMED_FIELD) {
+            serializer
+                .collect_map(values.map(|v| (v.element.schema_ancillary_type.as_ref().unwrap(), v)))
+        } else {
+            serializer.collect_seq(values)
+        }
+    }
+}
+
+impl SadSchemaElement {
+    /// JSON Schema of the element's JSON as SadElementValue serializes
+    /// it. References are to the definition under '$defs'
+    fn json_schema(&self) -> Value {
+        let items = self.items.as_deref().unwrap_or_default();
+        let integer = |minimum: Value, maximum: Value| json!({"type": "integer", "minimum": minimum, "maximum": maximum});
+        match self.schema_type.as_str() {
+            "Bool" => json!({"type": "boolean"}),
+            "U8" => integer(json!(u8::MIN), json!(u8::MAX)),
+            "U16" => integer(json!(u16::MIN), json!(u16::MAX)),
+            "U32" => integer(json!(u32::MIN), json!(u32::MAX)),
+            "U64" => integer(json!(u64::MIN), json!(u64::MAX)),
+            "I8" => integer(json!(i8::MIN), json!(i8::MAX)),
+            "I16" => integer(json!(i16::MIN), json!(i16::MAX)),
+            "I32" => integer(json!(i32::MIN), json!(i32::MAX)),
+            "I64" => integer(json!(i64::MIN), json!(i64::MAX)),
+            // Beyond the integers JSON parsers hold, rendered as decimal text
+            "U128" => json!({"type": "string", "pattern": "^[0-9]+$"}),
+            "I128" => json!({"type": "string", "pattern": "^-?[0-9]+$"}),
+            // NaN and infinities render as null
+            "F32" | "F64" => json!({"type": ["number", "null"]}),
+            "PublicKey" => json!({"type": "string", "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$"}),
+            SAD_REF => json!({
+                "$ref": format!("#/$defs/{}", self.schema_ancillary_type.as_ref().unwrap())
+            }),
+            "Vec" => json!({"type": "array", "items": items[0].json_schema()}),
+            "Array" => json!({
+                "type": "array",
+                "items": items[0].json_schema(),
+                "minItems": self.length,
+                "maxItems": self.length,
+            }),
+            "Option" => json!({"anyOf": [items[0].json_schema(), {"type": "null"}]}),
+            "Tuple" => SadSchemaElement::list_json_schema(items),
+            "CStruct" => SadSchemaElement::fields_json_schema(items),
+            SAD_NAMED_FIELD => items[0].json_schema(),
+            "HashMap" => json!({
+                "type": "object",
+                "additionalProperties": items[1].json_schema(),
+            }),
+            SAD_ENUM => self.variants_json_schema(),
+            SAD_TLV => json!({"type": "array", "items": self.variants_json_schema()}),
+            _ => json!({"type": "string"}),
+        }
+    }
+
+    /// JSON Schema of a list holding a value of each element in order
+    fn list_json_schema(elements: &[SadSchemaElement]) -> Value {
+        json!({
+            "type": "array",
+            "prefixItems": elements.iter().map(|e| e.json_schema()).collect::<Vec<_>>(),
+            "minItems": elements.len(),
+            "items": false,
+        })
+    }
+
+    /// JSON Schema of CStruct or Enum variant fields, an object when all
+    /// are named, else a list
+    fn fields_json_schema(fields: &[SadSchemaElement]) -> Value {
+        if fields.iter().all(|f| f.schema_type == SAD_NAMED_FIELD) {
+            let names = fields
+                .iter()
+                .map(|f| f.schema_ancillary_type.clone().unwrap())
+                .collect::<Vec<_>>();
+            json!({
+                "type": "object",
+                "properties": names
+                    .iter()
+                    .cloned()
+                    .zip(fields.iter().map(|f| f.json_schema()))
+                    .collect::<serde_json::Map<_, _>>(),
+                "required": names,
+                "additionalProperties": false,
+            })
+        } else {
+            SadSchemaElement::list_json_schema(fields)
+        }
+    }
+
+    /// JSON Schema of an Enum value, the name of a variant without fields,
+    /// or an object of the variant name to its fields
+    fn variants_json_schema(&self) -> Value {
+        let single = |name: &str, schema: Value| {
+            json!({
+                "type": "object",
+                "properties": {name: schema},
+                "required": [name],
+                "additionalProperties": false,
+            })
+        };
+        let mut variants = Vec::<Value>::new();
+        for variant in self.items.as_ref().unwrap() {
+            let name = variant.schema_ancillary_type.as_ref().unwrap();
+            let fields = variant.items.as_ref().unwrap();
+            variants.push(if fields.is_empty() {
+                json!({"const": name})
+            } else {
+                single(name, SadSchemaElement::fields_json_schema(fields))
+            });
+        }
+        variants.push(single(
+            SAD_UNKNOWN_VARIANT,
+            json!({"type": "integer", "minimum": 0}),
+        ));
+        json!({ "oneOf": variants })
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+/// SchemaItem represents a top level entity in the
+/// deserialization YAML. It contains the SadSchemaElements
+pub struct SadSchemaItem {
+    item_name: String,
+    items: Vec<SadSchemaElement>,
+}
+
+impl SadSchemaItem {
+    pub fn get_name(&self) -> &String {
+        &self.item_name
+    }
+
+    pub fn get_items(&self) -> &Vec<SadSchemaElement> {
+        &self.items
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SadSchema {
+    item_names: Vec<String>,
+    item_type_prefix: Vec<String>,
+    items: Vec<SadSchemaItem>,
+    types: SadSchemaTypes,
+}
+
+impl SadSchema {
+    /// Top level items, in declaration order
+    pub fn get_items(&self) -> &Vec<SadSchemaItem> {
+        &self.items
+    }
+
+    fn get_item_names(&self) -> &Vec<String> {
+        &self.item_names
+    }
+
+    /// Reference to the schema of named type definitions
+    pub fn get_types(&self) -> &SadSchemaTypes {
+        &self.types
+    }
+
+    /// Reference to item_name_TYPE labels
+    pub fn item_type_prefixes(&self) -> &Vec<String> {
+        &self.item_type_prefix
+    }
+
+    /// Element with references resolved to their definition
+    pub fn resolve<'a>(&'a self, element: &'a SadSchemaElement) -> &'a SadSchemaElement {
+        if element.schema_type == SAD_REF {
+            let name = element.schema_ancillary_type.as_ref().unwrap();
+            self.resolve(self.get_types().get(name).unwrap())
+        } else {
+            element
+        }
+    }
+
+    /// Gets item_name_type constructs, a Vec of
+    /// item_name_TYPE
+    fn gen_items_prefix(items: &Vec<SadSchemaItem>) -> Vec<String> {
+        let mut itypes = Vec::<String>::new();
+        for item in items {
+            if item.get_items().len() == 1 {
+                let element = &item.get_items()[0];
+                // References are labeled with the definition name
+                let type_label = if element.schema_type == SAD_REF {
+                    element.schema_ancillary_type.as_ref().unwrap()
+                } else {
+                    &element.schema_type
+                };
+                itypes.push(format!("{}_{}", item.get_name(), type_label))
+            } else {
+                panic!()
+            }
+        }
+        itypes
+    }
+
+    /// Given a header string vector, generate a summary
+    /// with item_type keys and hit count: HashMap<String, u64>
+    pub fn gen_type_summary(&self, inhdr: &Vec<String>) -> HashMap<String, u64> {
+        let mut summary = HashMap::<String, u64>::new();
+        let mut keys = Vec::<&String>::new();
+        for k in self.item_type_prefixes() {
+            summary.insert(k.to_string(), 0u64);
+            keys.push(k);
+        }
+        for t in inhdr {
+            for k in keys.iter() {
+                if t.contains(*k) {
+                    *summary.entry(k.to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    /// JSON Schema (draft 2020-12) of the JSON of the top level items,
+    /// with the named type definitions under '$defs'
+    pub fn json_schema(&self) -> Value {
+        let mut schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": self
+                .get_items()
+                .iter()
+                .map(|item| (item.get_name().clone(), item.get_items()[0].json_schema()))
+                .collect::<serde_json::Map<_, _>>(),
+            "required": self.get_item_names(),
+            "additionalProperties": false,
+        });
+        if !self.get_types().is_empty() {
+            schema["$defs"] = Value::Object(
+                self.get_types()
+                    .iter()
+                    .map(|(name, element)| (name.clone(), element.json_schema()))
+                    .collect(),
+            );
+        }
+        schema
+    }
+
+    /// Given a result of deserialization, generate a JSON
+    /// representation of all of its items
+    pub fn sad_to_json(&self, with_data: &Vec<SadValue>) -> SadTreeResult<Value> {
+        Ok(serde_json::to_value(SadItemValues {
+            schema: self,
+            values: with_data,
+            projection: None,
+        })?)
+    }
+
+    fn itemize(children: &Vec<Box<dyn Node>>) -> Vec<SadSchemaElement> {
+        let mut items = Vec::<SadSchemaElement>::new();
+        for c in children {
+            SadSchema::schema_item(c.as_ref(), &mut items)
+        }
+        items
+    }
+
+    fn itemize_variants(variants: &[SadVariant]) -> Vec<SadSchemaElement> {
+        variants
+            .iter()
+            .map(|v| {
+                SadSchemaElement::new(&SAD_VARIANT.to_string())
+                    .ancillary_type(v.name())
+                    .scalar(false)
+                    .items(SadSchema::itemize(v.children()))
+            })
+            .collect()
+    }
+
+    fn schema_item(node: &dyn Node, collect: &mut Vec<SadSchemaElement>) {
+        let schm_element = SadSchemaElement::new(&node.decl_type());
+        match node.decl_type().as_str() {
+            "HashMap" => {
+                let lp = node.downcast_ref::<SadHashMap>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "Vec" => {
+                let lp = node.downcast_ref::<SadVector>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "Array" => {
+                let lp = node.downcast_ref::<SadArray>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .length(lp.length)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "Option" => {
+                let lp = node.downcast_ref::<SadOption>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "Tuple" => {
+                let lp = node.downcast_ref::<SadTuple>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "CStruct" => {
+                let lp = node.downcast_ref::<SadStructure>().unwrap();
+
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "NamedField" => {
+                let lp = node.downcast_ref::<SadNamedField>().unwrap();
+                collect.push(
+                    schm_element
+                        .ancillary_type(&lp.sad_field_name)
+                        .scalar(false)
+                        .items(SadSchema::itemize(lp.children())),
+                )
+            }
+            "Ref" => {
+                let lp = node.downcast_ref::<SadRef>().unwrap();
+                collect.push(schm_element.ancillary_type(lp.name()).scalar(false))
+            }
+            "Enum" => {
+                let lp = node.downcast_ref::<SadEnum>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize_variants(lp.variants())),
+                )
+            }
+            "Tlv" => {
+                let lp = node.downcast_ref::<SadTlv>().unwrap();
+                collect.push(
+                    schm_element
+                        .scalar(false)
+                        .items(SadSchema::itemize_variants(lp.variants())),
+                )
+            }
+            _ => collect.push(schm_element.scalar(true)),
+        }
+    }
+
+    fn schema_for(name_id: String, node: &Box<dyn Node>) -> SadSchemaItem {
+        let mut ssi = Vec::<SadSchemaElement>::new();
+        SadSchema::schema_item(node.as_ref(), &mut ssi);
+        SadSchemaItem {
+            item_name: name_id,
+            items: ssi,
+        }
+    }
+
+    fn schema(tree: &SadTree) -> SadSchema {
+        let mut vi = Vec::<SadSchemaItem>::new();
+        let mut vn = Vec::<String>::new();
+        let mut index = 0;
+        for node in tree.children() {
+            let item_name = tree.varnames.get(index).unwrap().to_string();
+            let item = SadSchema::schema_for(item_name.clone(), node);
+            vi.push(item);
+            vn.push(item_name);
+            index += 1;
+        }
+
+        let mut types = SadSchemaTypes::new();
+        for (name, node) in tree.context.definitions() {
+            let mut def = Vec::<SadSchemaElement>::new();
+            SadSchema::schema_item(node, &mut def);
+            types.insert(name.clone(), def.pop().unwrap());
+        }
+
+        SadSchema {
+            item_type_prefix: SadSchema::gen_items_prefix(&vi),
+            items: vi,
+            item_names: vn,
+            types,
+        }
+    }
+}
+
+/// Path of a field of the value at path, the field itself at the top
+fn field_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+/// Deserialized values of the top level items of a declaration,
+/// serializing as a map of the item names to their values
+pub struct SadItemValues<'a> {
+    schema: &'a SadSchema,
+    values: &'a [SadValue],
+    projection: Option<&'a [usize]>,
+}
+
+impl<'a> SadItemValues<'a> {
+    /// Item names and values, of the projected items when projected
+    fn items(&self) -> impl Iterator<Item = (&'a String, SadElementValue<'a>)> + '_ {
+        let types = self.schema.get_types();
+        // Data deserialized for a projection covers only leading items
+        self.schema
+            .get_items()
+            .iter()
+            .zip(self.values)
+            .enumerate()
+            .filter(|(index, _)| match self.projection {
+                Some(indexes) => indexes.contains(index),
+                None => true,
+            })
+            .map(move |(_, (item, value))| {
+                (
+                    item.get_name(),
+                    SadElementValue {
+                        element: &item.get_items()[0],
+                        value,
+                        types,
+                    },
+                )
+            })
+    }
+
+    /// Public keys of the values, with the path of the field holding
+    /// each as in their JSON, such as `delegate.value` or `signers[2]`
+    pub fn public_keys(&self) -> Vec<(String, Pubkey)> {
+        let mut keys = Vec::<(String, Pubkey)>::new();
+        for (name, value) in self.items() {
+            value.public_keys(name, &mut keys)
+        }
+        keys
+    }
+}
+
+impl Serialize for SadItemValues<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.items())
+    }
+}
+
+/// Public struct for interacting deserialization to YAML construct declarations
+///
+/// Serializes as the declaration YAML text and the fields of any projection
+#[derive(Debug)]
+pub struct Deseriaizer {
+    sad_schema: SadSchema,
+    sad_tree: SadTree,
+    projection: Option<Vec<usize>>,
+    source: Yaml,
+}
+
+/// Serialized form of a Deseriaizer
+#[derive(Serialize, Deserialize)]
+struct SadDeclarationText {
+    declaration: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<String>>,
+}
+
+impl Serialize for Deseriaizer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut declaration = String::new();
+        YamlEmitter::new(&mut declaration)
+            .dump(&self.source)
+            .map_err(|e| ser::Error::custom(format!("{:?}", e)))?;
+        let names = self.sad_schema.get_item_names();
+        SadDeclarationText {
+            declaration,
+            fields: self
+                .projection
+                .as_ref()
+                .map(|indexes| indexes.iter().map(|i| names[*i].clone()).collect()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Deseriaizer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = SadDeclarationText::deserialize(deserializer)?;
+        let declaration =
+            Deseriaizer::from_yaml_str(&text.declaration).map_err(de::Error::custom)?;
+        match text.fields {
+            Some(fields) => declaration.with_fields(&fields),
+            None => Ok(declaration),
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+/// Deserializes the values of a declaration's items from data of any
+/// serde format holding them as rendered by `to_json`
+impl<'de> DeserializeSeed<'de> for &Deseriaizer {
+    type Value = Vec<SadValue>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.from_json(&Value::deserialize(deserializer)?)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Deseriaizer {
+    /// Panics if the declaration is malformed, see `try_new`
+    pub fn new(in_yaml: &Yaml) -> Self {
+        Self::try_new(in_yaml).unwrap()
+    }
+
+    /// Parses a declaration document
+    pub fn try_new(in_yaml: &Yaml) -> SadTreeResult<Self> {
+        let tree = SadTree::new(in_yaml)?;
+        let scm = SadSchema::schema(&tree);
+        Ok(Self {
+            sad_tree: tree,
+            sad_schema: scm,
+            projection: None,
+            source: in_yaml.clone(),
+        })
+    }
+
+    /// Parses the first declaration document of YAML text
+    pub fn from_yaml_str(in_str: &str) -> SadTreeResult<Self> {
+        let docs = YamlLoader::load_from_str(in_str)
+            .map_err(|e| SadTreeError::YamlError(e.to_string()))?;
+        Self::try_new(docs.first().ok_or(SadTreeError::ExpectedDescriptor)?)
+    }
+
+    /// Parses a bundled layout by name
+    pub fn from_layout(name: &str) -> SadTreeResult<Self> {
+        let docs = layout(name).ok_or_else(|| SadTreeError::UnknownImport(name.to_string()))?;
+        Self::try_new(&docs[0])
+    }
+
+    /// Name of the declaration
+    pub fn name(&self) -> &String {
+        self.sad_tree.get_name()
+    }
+
+    /// Restricts deserialization and output to the named top level
+    /// items, and the items that precede them in the data
+    pub fn with_fields(mut self, fields: &[String]) -> SadTreeResult<Self> {
+        let names = self.sad_schema.get_item_names();
+        let mut indexes = Vec::<usize>::new();
+        for field in fields {
+            match names.iter().position(|n| n == field) {
+                Some(index) => indexes.push(index),
+                None => return Err(SadTreeError::UnknownField(field.clone())),
+            }
+        }
+        indexes.sort_unstable();
+        indexes.dedup();
+        self.projection = Some(indexes);
+        Ok(self)
+    }
+
+    /// Count of leading top level items that are deserialized
+    fn item_count(&self) -> usize {
+        match &self.projection {
+            Some(indexes) => indexes.last().map_or(0, |i| i + 1),
+            None => self.sad_tree.children().len(),
+        }
+    }
+
+    /// Bytes of account data a projection needs, None when all of
+    /// the data is needed or the leading items vary in size
+    pub fn data_len(&self) -> Option<usize> {
+        self.projection.as_ref()?;
+        fixed_size_of_all(&self.sad_tree.children()[..self.item_count()])
+    }
+
+    pub fn schema(&self) -> &SadSchema {
+        &self.sad_schema
+    }
+
+    /// JSON Schema of `to_json` output, titled with the declaration
+    /// name and restricted to the items of any projection
+    pub fn json_schema(&self) -> Value {
+        let mut schema = self.sad_schema.json_schema();
+        schema["title"] = json!(self.name());
+        if let Some(indexes) = &self.projection {
+            let names = self.sad_schema.get_item_names();
+            let fields = indexes.iter().map(|i| &names[*i]).collect::<Vec<_>>();
+            schema["properties"]
+                .as_object_mut()
+                .unwrap()
+                .retain(|name, _| fields.contains(&name));
+            schema["required"] = json!(fields);
+        }
+        schema
+    }
+
+    /// Serializable view of deserialized values, with their item names
+    pub fn item_values<'a>(&'a self, with_data: &'a [SadValue]) -> SadItemValues<'a> {
+        SadItemValues {
+            schema: &self.sad_schema,
+            values: with_data,
+            projection: self.projection.as_deref(),
+        }
+    }
+
+    /// JSON of deserialized values, failing when the values do not
+    /// match the declaration
+    pub fn to_json(&self, with_data: &Vec<SadValue>) -> SadTreeResult<Value> {
+        Ok(serde_json::to_value(self.item_values(with_data))?)
+    }
+
+    /// Values of the items from their JSON, the inverse of `to_json`. All
+    /// of the items a projection deserializes must be present
+    pub fn from_json(&self, json_items: &Value) -> SadTreeResult<Vec<SadValue>> {
+        let types = self.sad_schema.get_types();
+        self.sad_schema
+            .get_items()
+            .iter()
+            .take(self.item_count())
+            .map(|item| match json_items.get(item.get_name()) {
+                Some(value) => item.get_items()[0].sad_from_json(value, types),
+                None => Err(SadTreeError::MissingValue(item.get_name().clone())),
+            })
+            .collect()
+    }
+
+    /// Deserializes data, failing when it is too short for the
+    /// declaration or holds invalid values
+    pub fn deser(&self, data: &mut &[u8]) -> SadTreeResult<Vec<SadValue>> {
+        let mut hm = Vec::<SadValue>::new();
+        for c in self.tree().children().iter().take(self.item_count()) {
+            c.deser(data, &mut hm)?
+        }
+        Ok(hm)
+    }
+
+    /// Deserializes data and converts it to JSON in one step
+    pub fn deser_to_json(&self, data: &[u8]) -> SadTreeResult<Value> {
+        self.to_json(&self.deser(&mut &data[..])?)
+    }
+
+    pub fn tree(&self) -> &SadTree {
+        &self.sad_tree
+    }
+}
+
+/// Name of the 'type:' of a YAML declaration type
+fn type_name(in_yaml: &Yaml) -> SadTreeResult<&str> {
+    let in_type = &in_yaml[SAD_YAML_TYPE];
+    in_type
+        .as_str()
+        .ok_or_else(|| SadTreeError::UnknownType(format!("{:?}", in_type)))
+}
+
+/// Dispatches YAML parse Node types
+fn parse(in_yaml: &Yaml, ctx: &SadParseContext) -> Result<Box<dyn Node>, SadTreeError> {
+    let in_hash = in_yaml.as_hash().ok_or(SadTreeError::ExpectedHashMap)?;
+    if in_hash.contains_key(SAD_TYPE_JSON.first().unwrap()) {
+        if let Some(s) = JUMP_TABLE.get(type_name(in_yaml)?) {
+            s(in_yaml, ctx)
+        } else {
+            SAD_JUMP_OTHER(in_yaml, ctx)
+        }
+    } else if in_hash.contains_key(&SAD_REF_JSON) {
+        // '$ref: Name' is shorthand for 'type: Ref'
+        SadRef::from_yaml(in_yaml, ctx)
+    } else {
+        Err(SadTreeError::ExpectedTypeKeyError(
+            SAD_YAML_TYPE.to_string(),
+        ))
+    }
+}
+
+/// JSON Schema, under '$defs', of a node of the declaration type
+fn node_json_schema(type_name: &str) -> Value {
+    let declaration_type = json!({"$ref": "#/$defs/DeclarationType"});
+    let declaration_types = json!({"type": "array", "items": declaration_type});
+    let int_type = json!({ "enum": SAD_ENUM_TAGS });
+    let count = json!({"type": "integer", "minimum": 0});
+    let variants = json!({"type": "array", "items": {"$ref": "#/$defs/Variant"}});
+    let mut properties = match type_name {
+        "Vec" | "Option" => json!({ SAD_YAML_CONTAINS: declaration_types }),
+        "Array" => json!({ SAD_YAML_CONTAINS: declaration_types, SAD_YAML_LENGTH: count }),
+        // Keys then values
+        "HashMap" => json!({SAD_YAML_FIELDS: {
+            "type": "array",
+            "items": declaration_type,
+            "minItems": 2,
+            "maxItems": 2,
+        }}),
+        "Tuple" | "CStruct" => json!({ SAD_YAML_FIELDS: declaration_types }),
+        SAD_NAMED_FIELD => json!({SAD_YAML_DESCRIPTOR: {
+            "type": "object",
+            "properties": {
+                SAD_YAML_NAME: {"type": "string"},
+                SAD_YAML_CONTAINS: declaration_type,
+            },
+            "required": [SAD_YAML_NAME, SAD_YAML_CONTAINS],
+            "additionalProperties": false,
+        }}),
+        SAD_REF => json!({SAD_YAML_REF: {"type": "string"}}),
+        SAD_ENUM => json!({ SAD_YAML_TAG: int_type, SAD_YAML_VARIANTS: variants }),
+        SAD_TLV => json!({
+            SAD_YAML_TAG: int_type,
+            SAD_YAML_LENGTH_TYPE: int_type,
+            SAD_YAML_TERMINATOR: count,
+            SAD_YAML_VARIANTS: variants,
+        }),
+        _ => json!({}),
+    };
+    properties[SAD_YAML_TYPE] = json!({ "const": type_name });
+    // Integer types of Enums and Tlvs have defaults
+    let required = properties
+        .as_object()
+        .unwrap()
+        .keys()
+        .filter(|k| {
+            ![SAD_YAML_TAG, SAD_YAML_LENGTH_TYPE, SAD_YAML_TERMINATOR].contains(&k.as_str())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// JSON Schema (draft 2020-12) of declaration documents. The declaration
+/// types are those of the parser's jump table, and of SadValue for scalars
+pub fn declaration_json_schema() -> Value {
+    let mut node_types = JUMP_TABLE
+        .keys()
+        .filter(|k| k.as_str() != "other")
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    node_types.sort_unstable();
+    // Types without a parser of their own are parsed as scalars
+    let scalar_types = SadValue::VARIANTS
+        .iter()
+        .filter(|v| !JUMP_TABLE.contains_key(**v))
+        .collect::<Vec<_>>();
+    let mut defs = serde_json::Map::<String, Value>::new();
+    // Declaration types are told apart by 'type:', for errors at their keys
+    let mut type_names = scalar_types.iter().map(|t| json!(t)).collect::<Vec<_>>();
+    let mut conditionals = vec![json!({
+        "if": {"properties": {SAD_YAML_TYPE: {"enum": scalar_types}}},
+        "then": {"$ref": "#/$defs/Scalar"},
+    })];
+    defs.insert(
+        String::from("Scalar"),
+        json!({
+            "type": "object",
+            "properties": {SAD_YAML_TYPE: {"enum": scalar_types}},
+            "required": [SAD_YAML_TYPE],
+            "additionalProperties": false,
+        }),
+    );
+    for type_name in node_types {
+        type_names.push(json!(type_name));
+        conditionals.push(json!({
+            "if": {"properties": {SAD_YAML_TYPE: {"const": type_name}}},
+            "then": {"$ref": format!("#/$defs/{}", type_name)},
+        }));
+        defs.insert(type_name.to_string(), node_json_schema(type_name));
+    }
+    // '$ref: Name' is shorthand for 'type: Ref'
+    let type_reference = json!({
+        "type": "object",
+        "properties": {SAD_YAML_REF: {"type": "string"}},
+        "required": [SAD_YAML_REF],
+        "additionalProperties": false,
+    });
+    defs.insert(
+        String::from("DeclarationType"),
+        json!({
+            "type": "object",
+            "if": {"required": [SAD_YAML_TYPE]},
+            "then": {
+                "properties": {SAD_YAML_TYPE: {"enum": type_names}},
+                "allOf": conditionals,
+            },
+            "else": type_reference,
+        }),
+    );
+    defs.insert(
+        String::from("Variant"),
+        json!({
+            "type": "object",
+            "properties": {
+                SAD_YAML_NAME: {"type": "string"},
+                SAD_YAML_VALUE: {"type": "integer", "minimum": 0},
+                SAD_YAML_FIELDS: {"type": "array", "items": {"$ref": "#/$defs/DeclarationType"}},
+            },
+            "required": [SAD_YAML_NAME],
+            "additionalProperties": false,
+        }),
+    );
+
+    let names = json!({"anyOf": [
+        {"type": "string"},
+        {"type": "array", "items": {"type": "string"}},
+    ]});
+    let reserved = SAD_RESERVED_KEYS
+        .iter()
+        .map(|key| {
+            let schema = match *key {
+                SAD_YAML_TYPES => json!({
+                    "type": "object",
+                    "additionalProperties": {"$ref": "#/$defs/DeclarationType"},
+                }),
+                SAD_YAML_IMPORT | SAD_YAML_INCLUDE => names.clone(),
+                SAD_YAML_MATCH => match_json_schema(),
+                SAD_YAML_ENCODING => json!({"enum": SadEncoding::NAMES}),
+                _ => json!(true),
+            };
+            (key.to_string(), schema)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "sad declaration",
+        "type": "object",
+        "properties": reserved,
+        // The descriptor, a list of named items or a reference to a CStruct
+        "additionalProperties": {
+            "if": {"type": "array"},
+            "then": {"items": {
+                "type": "object",
+                "minProperties": 1,
+                "maxProperties": 1,
+                "additionalProperties": {"$ref": "#/$defs/DeclarationType"},
+            }},
+            "else": type_reference,
+        },
+        "not": {"propertyNames": {"enum": SAD_RESERVED_KEYS}},
+        "$defs": defs,
+    })
+}
+
+/// JSON of a YAML declaration document, to validate against the
+/// declaration_json_schema. Keys other than strings are their YAML text
+pub fn declaration_json(in_yaml: &Yaml) -> Value {
+    match in_yaml {
+        Yaml::Real(_) => in_yaml.as_f64().map_or(Value::Null, |f| json!(f)),
+        Yaml::Integer(i) => json!(i),
+        Yaml::String(s) => json!(s),
+        Yaml::Boolean(b) => json!(b),
+        Yaml::Array(list) => Value::Array(list.iter().map(declaration_json).collect()),
+        Yaml::Hash(hash) => Value::Object(
+            hash.iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        Yaml::String(s) => s.clone(),
+                        other => match declaration_json(other) {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        },
+                    };
+                    (key, declaration_json(v))
+                })
+                .collect(),
+        ),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use base64::decode;
+    use borsh::BorshSerialize;
+    use gadgets_common::load_yaml_file;
+    use solana_sdk::pubkey::Pubkey;
+    use strum::VariantNames;
+    use yaml_rust::YamlLoader;
+
+    const INDEX_HASHMAP_STRING_U128: usize = 2;
+    const INDEX_VECTOR_STRING: usize = 3;
+    const INDEX_VECTOR_U32: usize = 4;
+    const INDEX_TUPLE_STRING_U128: usize = 5;
+    const INDEX_STRUCT_STRING_U32: usize = 6;
+    const INDEX_PUBLICKEY: usize = 9;
+    const INDEX_TYPEREF: usize = 10;
+    const INDEX_ENUM: usize = 11;
+    const INDEX_TLV: usize = 12;
+
+    #[derive(BorshSerialize)]
+    struct OfTuple(String, u128);
+
+    #[derive(BorshSerialize)]
+    struct OfStruct {
+        name: String,
+        age: u32,
+    }
+
+    #[derive(BorshSerialize)]
+    enum OfEnum {
+        Initialize,
+        Transfer { amount: u64 },
+        Memo(String, u8),
+    }
+
+    /// Recursive type, borsh derive can't handle it so serialize by hand
+    struct OfNode {
+        value: u32,
+        children: Vec<OfNode>,
+    }
+
+    impl OfNode {
+        fn try_to_vec(&self) -> std::io::Result<Vec<u8>> {
+            let mut data = self.value.try_to_vec()?;
+            data.extend((self.children.len() as u32).try_to_vec()?);
+            for c in &self.children {
+                data.extend(c.try_to_vec()?);
+            }
+            Ok(data)
+        }
+    }
+    /// vscode changes cwd depending on running test or debugging test
+    fn get_runner_yaml() -> Vec<Yaml> {
+        if std::env::current_dir().unwrap().ends_with("gadgets-sad") {
+            load_yaml_file("../yaml_samps/runner.yml").unwrap()
+        } else {
+            load_yaml_file("./yaml_samps/runner.yml").unwrap()
+        }
+    }
+
+    /// vscode changes cwd depending on running test or debugging test
+    fn get_sample_yaml() -> Vec<Yaml> {
+        if std::env::current_dir().unwrap().ends_with("gadgets-sad") {
+            load_yaml_file(
+                "../../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml",
+            )
+            .unwrap()
+        } else {
+            load_yaml_file("../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml")
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn test_deserialization_pass() {
+        println!("{:?}", std::env::current_dir().unwrap());
+        let pacc = "ASUAAAABAAAABAAAAEFLZXkVAAAATWludGVkIGtleSB2YWx1ZSBwYWlyAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==";
+        let pacv = decode(pacc).unwrap();
+        let result = get_sample_yaml();
+        let desc = Deseriaizer::new(&result[0]);
+        // println!("{:?}", desc.schema().flat_header(None));
+        let deserialize_vector = desc.deser(&mut pacv.as_slice()).unwrap();
+        println!("{:?}", deserialize_vector);
+        println!(
+            "{:?}",
+            desc.schema()
+                .sad_to_json(&deserialize_vector)
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_leaf_node_pass() {
+        for v in SadValue::VARIANTS.iter() {
+            let vs = *v;
+            let d = format!("{}: {}", "type", vs);
+            let docs = YamlLoader::load_from_str(&d).unwrap();
+            let doc = &docs[0]; // select the first document
+            let sl = SadLeaf::from_yaml(doc, &SadParseContext::default());
+            assert!(sl.is_ok());
+            println!("{:?}", sl);
+        }
+    }
+
+    #[test]
+    fn test_to_string() {
+        let docs = YamlLoader::load_from_str(&format!("{}", SAD_YAML_TYPE)).unwrap();
+        println!("{:?}", docs);
+    }
+
+    #[test]
+    fn test_scalars_pass() {
+        let mut pos = 0;
+        let pos_end = 14;
+        for v in SadValue::VARIANTS.iter() {
+            if pos == pos_end {
+                break;
+            }
+            pos += 1;
+            let vs = *v;
+            let d = format!("{}: {}", "type", vs);
+            let docs = YamlLoader::load_from_str(&d).unwrap();
+            let result = parse(&docs[0], &SadParseContext::default());
+            assert!(result.is_ok());
+        }
+    }
+    #[test]
+    fn test_runner_pass() {
+        let result = get_runner_yaml();
+        for body in result {
+            let desc = Deseriaizer::new(&body);
+            assert!(
+                !desc.schema().get_item_names().is_empty(),
+                "{}",
+                desc.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_hashmap_pass() {
+        let mut mhmap = HashMap::<&str, u128>::new();
+        mhmap.insert("foo", 1u128);
+        mhmap.insert("bar", 2u128);
+        mhmap.insert("baz", 3u128);
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_HASHMAP_STRING_U128]);
+        let data = mhmap.try_to_vec().unwrap();
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        println!("{:?}", deserialize_vector);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.schema().sad_to_json(&deserialize_vector).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_vector_string_pass() {
+        let mut mhmap = Vec::<String>::new();
+        mhmap.push(String::from("foo"));
+        mhmap.push(String::from("bar"));
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_VECTOR_STRING]);
+        let data = mhmap.try_to_vec().unwrap();
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_vector_u32_pass() {
+        let mut mhmap = Vec::<u32>::new();
+        mhmap.push(1u32);
+        mhmap.push(2u32);
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_VECTOR_U32]);
+        let data = mhmap.try_to_vec().unwrap();
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        println!("{:?}", deserialize_vector);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fallible_constructors_pass() {
+        let desc = Deseriaizer::from_yaml_str(
+            "counter:\n  - count:\n      type: U32\n  - label:\n      type: String\n",
+        )
+        .unwrap();
+        assert_eq!(desc.name(), "counter");
+        let data = (7u32, "seven".to_string()).try_to_vec().unwrap();
+        assert_eq!(
+            desc.deser_to_json(&data).unwrap(),
+            json!({"count": 7, "label": "seven"})
+        );
+        assert!(matches!(
+            Deseriaizer::from_yaml_str("counter:\n  - count:\n      type: Nope\n"),
+            Err(SadTreeError::UnknownType(_))
+        ));
+        assert!(matches!(
+            Deseriaizer::from_yaml_str("counter: [\n"),
+            Err(SadTreeError::YamlError(_))
+        ));
+        assert!(matches!(
+            Deseriaizer::from_layout("nope"),
+            Err(SadTreeError::UnknownImport(_))
+        ));
+        assert!(Deseriaizer::from_layout("spl_token_mint").is_ok());
+    }
+
+    #[test]
+    fn test_short_data_fail() {
+        let desc = Deseriaizer::from_yaml_str(
+            "counter:\n  - count:\n      type: U32\n  - label:\n      type: String\n",
+        )
+        .unwrap();
+        let data = (7u32, "seven".to_string()).try_to_vec().unwrap();
+        assert!(matches!(
+            desc.deser(&mut &data[..data.len() - 1]),
+            Err(SadTreeError::InsufficientData(5, 4))
+        ));
+        assert!(matches!(
+            desc.deser_to_json(&[]),
+            Err(SadTreeError::InsufficientData(4, 0))
+        ));
+        let desc = Deseriaizer::from_layout("spl_token_account").unwrap();
+        assert!(desc.deser_to_json(&[0u8; 100]).is_err());
+        let desc = Deseriaizer::from_yaml_str(
+            "maybe:\n  - value:\n      type: Option\n      contains:\n        - type: U8\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            desc.deser_to_json(&[]),
+            Err(SadTreeError::InsufficientData(1, 0))
+        ));
+        // Option tags other than 0 and 1 are not borsh or bincode
+        assert!(matches!(
+            desc.deser_to_json(&[2, 7]),
+            Err(SadTreeError::InvalidData(..))
+        ));
+    }
+
+    #[test]
+    fn test_tuple_pass() {
+        let mhmap = OfTuple("Foo".to_string(), 19u128);
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_TUPLE_STRING_U128]);
+
+        let data = mhmap.try_to_vec().unwrap();
+        println!("{:?}", data);
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        println!("deser {:?}", deserialize_vector);
+        println!("types{:?}", desc.schema().item_type_prefixes());
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_struct_pass() {
+        let mhmap = OfStruct {
+            name: "Frank".to_string(),
+            age: 64,
+        };
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_STRUCT_STRING_U32]);
+        let data = mhmap.try_to_vec().unwrap();
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        println!("deser {:?}", deserialize_vector);
+        println!("types{:?}", desc.schema().item_type_prefixes());
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn pubkey_pass() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_PUBLICKEY]);
+        let pk = Pubkey::from_str("A94wMjV54C8f8wn7zL8TxNCdNiGoq7XSN7vWGrtd4vwU").unwrap();
+        let pk_ser = pk.try_to_vec().unwrap();
+        println!("{:?}", pk_ser);
+        let deserialize_vector = desc.deser(&mut pk_ser.as_slice()).unwrap();
+        println!("deser {:?}", deserialize_vector);
+        println!("types{:?}", desc.schema().item_type_prefixes());
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_typeref_pass() {
+        let owner = OfStruct {
+            name: "Frank".to_string(),
+            age: 64,
+        };
+        let delegate = OfStruct {
+            name: "Bob".to_string(),
+            age: 32,
+        };
+        let tree = OfNode {
+            value: 1,
+            children: vec![
+                OfNode {
+                    value: 2,
+                    children: vec![],
+                },
+                OfNode {
+                    value: 3,
+                    children: vec![OfNode {
+                        value: 4,
+                        children: vec![],
+                    }],
+                },
+            ],
+        };
+        let mut data = owner.try_to_vec().unwrap();
+        data.extend(delegate.try_to_vec().unwrap());
+        data.extend(tree.try_to_vec().unwrap());
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_TYPEREF]);
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        assert_eq!(
+            desc.schema().item_type_prefixes(),
+            &vec![
+                "owner_Person".to_string(),
+                "delegate_Person".to_string(),
+                "tree_Node".to_string()
+            ]
+        );
+        let json_out = desc.to_json(&deserialize_vector).unwrap();
+        assert_eq!(json_out["owner"], json!({"name": "Frank", "age": 64}));
+        assert_eq!(json_out["delegate"], json!({"name": "Bob", "age": 32}));
+        assert_eq!(
+            json_out["tree"]["children"][1]["children"][0],
+            json!({"value": 4, "children": []})
+        );
+        assert_eq!(desc.from_json(&json_out).unwrap(), deserialize_vector);
+        assert!(jsonschema::is_valid(&desc.json_schema(), &json_out));
+        println!("{}", serde_json::to_string_pretty(&json_out).unwrap());
+    }
+
+    #[test]
+    fn test_enum_pass() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_ENUM]);
+        let cases = [
+            (OfEnum::Initialize, json!("Initialize")),
+            (
+                OfEnum::Transfer { amount: 500 },
+                json!({"Transfer": {"amount": 500}}),
+            ),
+            (
+                OfEnum::Memo("Hi".to_string(), 7),
+                json!({"Memo": ["Hi", 7]}),
+            ),
+        ];
+        for (instruction, expected) in cases {
+            let mut data = instruction.try_to_vec().unwrap();
+            // 'wide' has a U32 tag and an explicit discriminator value
+            data.extend(2u32.try_to_vec().unwrap());
+            data.extend(9u16.try_to_vec().unwrap());
+            let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+            let json_out = desc.to_json(&deserialize_vector).unwrap();
+            assert_eq!(json_out["instruction"], expected);
+            assert_eq!(json_out["wide"], json!({"Two": [9]}));
+            assert_eq!(desc.from_json(&json_out).unwrap(), deserialize_vector);
+            assert!(jsonschema::is_valid(&desc.json_schema(), &json_out));
+        }
+        assert_eq!(
+            desc.schema().item_type_prefixes(),
+            &vec!["instruction_Enum".to_string(), "wide_Enum".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_enum_unknown_variant_pass() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_ENUM]);
+        let mut data = vec![9u8];
+        data.extend(0u32.try_to_vec().unwrap());
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        let json_out = desc.to_json(&deserialize_vector).unwrap();
+        assert_eq!(json_out["instruction"], json!({"unknown_variant": 9}));
+        assert_eq!(json_out["wide"], json!("Zero"));
+        assert_eq!(desc.from_json(&json_out).unwrap(), deserialize_vector);
+        assert!(jsonschema::is_valid(&desc.json_schema(), &json_out));
+    }
+
+    #[test]
+    fn test_enum_tag_fail() {
+        let docs = YamlLoader::load_from_str(
+            "bad:\n  - e:\n      type: Enum\n      tag: I8\n      variants:\n        - name: A\n",
+        )
+        .unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::ExpectedEnumTag(_)) => {}
+            other => panic!("Expected enum tag error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tlv_pass() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_TLV]);
+        let mut data = vec![1u8, 2u8];
+        for (record_type, value) in [
+            (2u16, 500u64.try_to_vec().unwrap()),
+            (5u16, vec![1u8, 2u8, 3u8]),
+            (8u16, vec![1u8]),
+        ] {
+            data.extend(record_type.try_to_vec().unwrap());
+            data.extend((value.len() as u16).try_to_vec().unwrap());
+            data.extend(value);
+        }
+        // Records end at the terminator, not the end of the data
+        data.extend([0u8; 8]);
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        let json_out = desc.to_json(&deserialize_vector).unwrap();
+        assert_eq!(json_out["signers"], json!([1, 2]));
+        assert_eq!(
+            json_out["extensions"],
+            json!([{"Amount": {"amount": 500}}, {"unknown_variant": 5}, {"Flag": [true]}])
+        );
+        assert_eq!(desc.from_json(&json_out).unwrap(), deserialize_vector);
+        assert!(jsonschema::is_valid(&desc.json_schema(), &json_out));
+        assert_eq!(
+            desc.schema().item_type_prefixes(),
+            &vec!["signers_Array".to_string(), "extensions_Tlv".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_tlv_truncated_fail() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_TLV]);
+        let mut data = vec![1u8, 2u8];
+        data.extend(2u16.try_to_vec().unwrap());
+        data.extend(8u16.try_to_vec().unwrap());
+        data.extend(500u64.try_to_vec().unwrap());
+        // Value shorter than its declared length
+        assert!(matches!(
+            desc.deser(&mut &data[..data.len() - 1]),
+            Err(SadTreeError::InsufficientData(8, 7))
+        ));
+        // Header cut short after the type
+        assert!(matches!(
+            desc.deser(&mut &data[..5]),
+            Err(SadTreeError::InsufficientData(4, 3))
+        ));
+        // Complete records followed by the terminator
+        data.extend([0u8; 2]);
+        assert!(desc.deser(&mut data.as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_array_length_fail() {
+        let docs = YamlLoader::load_from_str(
+            "bad:\n  - a:\n      type: Array\n      contains:\n        - type: U8\n",
+        )
+        .unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::ExpectedArrayLength) => {}
+            other => panic!("Expected array length error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bincode_encoding_pass() {
+        let docs = YamlLoader::load_from_str(
+            "encoding: bincode\nbin:\n  - name:\n      type: String\n  - ages:\n      type: Vec\n      contains:\n        - type: U16\n  - state:\n      type: Enum\n      variants:\n        - name: A\n        - name: B\n",
+        )
+        .unwrap();
+        let desc = Deseriaizer::new(&docs[0]);
+        let mut data = 2u64.try_to_vec().unwrap();
+        data.extend(b"hi");
+        data.extend(2u64.try_to_vec().unwrap());
+        data.extend([7u8, 0, 9, 0]);
+        data.extend(1u32.try_to_vec().unwrap());
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        assert_eq!(
+            desc.to_json(&deserialize_vector).unwrap(),
+            json!({"name": "hi", "ages": [7, 9], "state": "B"})
+        );
+    }
+
+    #[test]
+    fn test_unknown_encoding_fail() {
+        let docs =
+            YamlLoader::load_from_str("encoding: json\nbad:\n  - a:\n      type: U8\n").unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::UnknownEncoding(_)) => {}
+            other => panic!("Expected encoding error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_declaration_fail() {
+        assert!(matches!(
+            Deseriaizer::from_yaml_str("bad:\n  - a:\n      type: 5\n"),
+            Err(SadTreeError::UnknownType(_))
+        ));
+        assert!(matches!(
+            Deseriaizer::from_yaml_str(
+                "bad:\n  - a:\n      type: CStruct\n      fields:\n        - type: NamedField\n          descriptor:\n            contains:\n              type: U8\n",
+            ),
+            Err(SadTreeError::ExpectedFieldName)
+        ));
+        assert!(matches!(
+            Deseriaizer::from_yaml_str("bad:\n  - 5\n"),
+            Err(SadTreeError::ExpectedHashMap)
+        ));
+    }
+
+    #[test]
+    fn test_fields_projection_pass() {
+        let docs = YamlLoader::load_from_str(
+            "types:\n  Pair:\n    type: Tuple\n    fields:\n      - type: U8\n      - type: U16\nproj:\n  - count:\n      type: U32\n  - owner:\n      type: PublicKey\n  - pair:\n      $ref: Pair\n  - name:\n      type: String\n  - flag:\n      type: Bool\n",
+        )
+        .unwrap();
+        let fields = |f: &[&str]| f.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(Deseriaizer::new(&docs[0]).data_len(), None);
+        let desc = Deseriaizer::new(&docs[0])
+            .with_fields(&fields(&["pair", "owner"]))
+            .unwrap();
+        assert_eq!(desc.data_len(), Some(39));
+        let owner = Pubkey::new_unique();
+        let mut data = 5u32.try_to_vec().unwrap();
+        data.extend(owner.to_bytes());
+        data.extend([1u8, 2, 0]);
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        assert_eq!(
+            desc.to_json(&deserialize_vector).unwrap(),
+            json!({"owner": owner.to_string(), "pair": [1, 2]})
+        );
+        let desc = Deseriaizer::new(&docs[0])
+            .with_fields(&fields(&["flag"]))
+            .unwrap();
+        assert_eq!(desc.data_len(), None);
+        match Deseriaizer::new(&docs[0]).with_fields(&fields(&["missing"])) {
+            Err(SadTreeError::UnknownField(f)) => assert_eq!(f, "missing"),
+            other => panic!("Expected unknown field error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_serde_pass() {
+        let mut mhmap = HashMap::<&str, u128>::new();
+        mhmap.insert("foo", u128::MAX);
+        mhmap.insert("bar", 2u128);
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_HASHMAP_STRING_U128]);
+        let data = mhmap.try_to_vec().unwrap();
+        let deserialize_vector = desc.deser(&mut data.as_slice()).unwrap();
+        let json_out = desc.to_json(&deserialize_vector).unwrap();
+        assert_eq!(json_out.as_object().unwrap().len(), 1);
+        let (_, entries) = json_out.as_object().unwrap().iter().next().unwrap();
+        assert_eq!(entries["foo"], json!(u128::MAX.to_string()));
+        // Map entries reload in key order
+        let reloaded = desc.from_json(&json_out).unwrap();
+        assert_eq!(desc.to_json(&reloaded).unwrap(), json_out);
+
+        // Values reload with the types of the declaration from any format
+        let reseeded = (&desc)
+            .deserialize(&mut serde_json::Deserializer::from_str(
+                &serde_json::to_string(&desc.item_values(&deserialize_vector)).unwrap(),
+            ))
+            .unwrap();
+        assert_eq!(desc.to_json(&reseeded).unwrap(), json_out);
+        match desc.from_json(&json!({})) {
+            Err(SadTreeError::MissingValue(_)) => {}
+            other => panic!("Expected missing value error, got {:?}", other),
+        }
+
+        // A Deseriaizer reloads from its declaration and projection
+        let desc = Deseriaizer::from_layout("spl_token_account")
+            .unwrap()
+            .with_fields(&["owner".to_string()])
+            .unwrap();
+        let reloaded =
+            serde_json::from_str::<Deseriaizer>(&serde_json::to_string(&desc).unwrap()).unwrap();
+        assert_eq!(reloaded.name(), desc.name());
+        assert_eq!(reloaded.data_len(), Some(64));
+        assert_eq!(reloaded.schema(), desc.schema());
+    }
+
+    #[test]
+    fn test_json_schema_pass() {
+        let result = get_runner_yaml();
+        let mut mhmap = HashMap::<&str, u128>::new();
+        mhmap.insert("foo", u128::MAX);
+        let desc = Deseriaizer::new(&result[INDEX_HASHMAP_STRING_U128]);
+        let json_out = desc.deser_to_json(&mhmap.try_to_vec().unwrap()).unwrap();
+        let schema = desc.json_schema();
+        assert_eq!(&schema["title"], desc.name());
+        assert!(jsonschema::is_valid(&schema, &json_out));
+        // U128s are decimal text
+        let (name, _) = json_out.as_object().unwrap().iter().next().unwrap();
+        assert!(!jsonschema::is_valid(&schema, &json!({ name: {"foo": 1} })));
+
+        let tuple = OfTuple("Hi".to_string(), 7);
+        let desc = Deseriaizer::new(&result[INDEX_TUPLE_STRING_U128]);
+        let json_out = desc.deser_to_json(&tuple.try_to_vec().unwrap()).unwrap();
+        assert!(jsonschema::is_valid(&desc.json_schema(), &json_out));
+        assert!(!jsonschema::is_valid(&desc.json_schema(), &json!({})));
+
+        // Definitions are referenced under '$defs' and projections
+        // describe only their items
+        let desc = Deseriaizer::new(&result[INDEX_TYPEREF]);
+        let schema = desc.json_schema();
+        assert_eq!(
+            schema["properties"]["owner"],
+            json!({"$ref": "#/$defs/Person"})
+        );
+        assert_eq!(
+            schema["$defs"]["Person"]["required"],
+            json!(["name", "age"])
+        );
+        let desc = desc.with_fields(&["delegate".to_string()]).unwrap();
+        let schema = desc.json_schema();
+        assert_eq!(schema["required"], json!(["delegate"]));
+        assert_eq!(schema["properties"].as_object().unwrap().len(), 1);
+
+        let desc = Deseriaizer::from_layout("spl_token_account").unwrap();
+        let schema = desc.json_schema();
+        assert!(jsonschema::is_valid(
+            &schema,
+            &desc.deser_to_json(&[0u8; 165]).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_declaration_json_schema_pass() {
+        let schema = declaration_json_schema();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        assert_eq!(
+            schema["$defs"]["Scalar"]["properties"]["type"]["enum"],
+            json!([
+                "String", "Bool", "U8", "U16", "U32", "U64", "U128", "I8", "I16", "I32", "I64",
+                "I128", "F32", "F64"
+            ])
+        );
+        let mut docs = get_runner_yaml();
+        for name in crate::library::layout_names() {
+            docs.extend(layout(name).unwrap());
+        }
+        for doc in &docs {
+            let errors = validator
+                .iter_errors(&declaration_json(doc))
+                .map(|e| format!("{} {}", e.instance_path, e))
+                .collect::<Vec<_>>();
+            assert!(errors.is_empty(), "{:?}", errors);
+        }
+        for bad in [
+            "bad:\n  - a:\n      type: U256\n",
+            "bad:\n  - a:\n      type: Vec\n",
+            "bad:\n  - a:\n      type: Array\n      contains:\n        - type: U8\n",
+            "bad:\n  - a:\n      type: Enum\n      tag: I8\n      variants: []\n",
+            "bad:\n  - a:\n      type: U8\n    b:\n      type: U8\n",
+            "bad:\n  - a:\n      type: CStruct\n      feilds: []\n",
+            "encoding: json\nbad:\n  - a:\n      type: U8\n",
+            "match:\n  size: 3\nbad:\n  - a:\n      type: U8\n",
+            "types: {}\n",
+        ] {
+            let doc = &YamlLoader::load_from_str(bad).unwrap()[0];
+            assert!(!validator.is_valid(&declaration_json(doc)), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_cyclic_typeref_fail() {
+        let docs = YamlLoader::load_from_str(
+            "types:\n  A:\n    type: Tuple\n    fields:\n      - $ref: B\n  B:\n    type: Tuple\n    fields:\n      - type: U8\n      - $ref: A\ncyclic:\n  - a:\n      $ref: A\n",
+        )
+        .unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::CyclicTypeReference(_)) => {}
+            other => panic!("Expected cyclic reference error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_typeref_fail() {
+        let docs = YamlLoader::load_from_str("unknown:\n  - a:\n      $ref: Missing\n").unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::UnknownTypeReference(name)) => assert_eq!(name, "Missing"),
+            other => panic!("Expected unknown reference error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_import_layout_pass() {
+        let authority = Pubkey::from_str("A94wMjV54C8f8wn7zL8TxNCdNiGoq7XSN7vWGrtd4vwU").unwrap();
+        let mut data = 1u32.try_to_vec().unwrap();
+        data.extend(authority.try_to_vec().unwrap());
+        data.extend(1_000_000u64.try_to_vec().unwrap());
+        data.extend([6u8, 1u8]);
+        data.extend(0u32.try_to_vec().unwrap());
+        data.extend([0u8; 32]);
+        assert_eq!(data.len(), 82);
+        let docs = YamlLoader::load_from_str(
+            "import: spl_token_mint\nminted:\n  - mint:\n      $ref: SplTokenMint\n",
+        )
+        .unwrap();
+        let desc = Deseriaizer::new(&docs[0]);
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["mint"]["supply"], json!(1_000_000u64));
+        assert_eq!(
+            json_out["mint"]["mint_authority"]["value"],
+            json!(authority.to_string())
+        );
+        // The bundled layout descriptor flattens the fields to the top level
+        let desc = Deseriaizer::new(&layout("spl_token_mint").unwrap()[0]);
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["decimals"], json!(6u8));
+        assert_eq!(json_out["freeze_authority"]["is_some"], json!(0u32));
+    }
+
+    #[test]
+    fn test_unknown_import_fail() {
+        let docs = YamlLoader::load_from_str(
+            "import: [spl_common, nothere]\nbad:\n  - a:\n      type: U8\n",
+        )
+        .unwrap();
+        match SadTree::new(&docs[0]) {
+            Err(SadTreeError::UnknownImport(name)) => assert_eq!(name, "nothere"),
+            other => panic!("Expected unknown import error, got {:?}", other),
+        }
+    }
+}
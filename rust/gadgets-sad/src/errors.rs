@@ -0,0 +1,113 @@
+use {
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    thiserror::Error,
+};
+
+#[derive(Error, Debug)]
+pub enum SadTreeError {
+    #[error("Don't know type {0}")]
+    UnknownType(String),
+    #[error("Expected 'type:' as first entry, found {0}")]
+    ExpectedTypeKeyError(String),
+    #[error("Expected YAML HashMap")]
+    ExpectedHashMap,
+    #[error("Expected YAML Array")]
+    ExpectedArray,
+    #[error("Expected YAML HashMap or Array")]
+    ExpectedHashMapOrArray,
+    #[error("Expected HashMap fields")]
+    ExpectedHashMapFields,
+    #[error("Expected Vec contains")]
+    ExpectedVecContains,
+    #[error("Expected Tuple fields")]
+    ExpectedTupleFields,
+    #[error("Expected CStruct fields")]
+    ExpectedCStructFields,
+    #[error("Expected NamedField 'name:'")]
+    ExpectedFieldName,
+    #[error("Expected Length Prefix Schema Ancillary Type")]
+    ExpectedLengthSchemaType,
+    #[error("Expected declaration descriptor")]
+    ExpectedDescriptor,
+    #[error("Expected 'types:' to be a HashMap of named definitions")]
+    ExpectedTypesHashMap,
+    #[error("Expected '$ref:' type name")]
+    ExpectedRefName,
+    #[error("Reference to undefined type {0}")]
+    UnknownTypeReference(String),
+    #[error("Type {0} references itself without an intervening Vec, HashMap, Option, Enum or Tlv")]
+    CyclicTypeReference(String),
+    #[error("Expected 'import:' layout name or list of names")]
+    ExpectedImportName,
+    #[error("No bundled layout named {0}")]
+    UnknownImport(String),
+    #[error("Layout {0} imports itself")]
+    CyclicImport(String),
+    #[error("Expected Enum 'tag:' of U8, U16, U32 or U64, found {0}")]
+    ExpectedEnumTag(String),
+    #[error("Expected Enum variants, each with a 'name:' and optional 'value:' and 'fields:'")]
+    ExpectedEnumVariants,
+    #[error("Expected 'encoding:' of borsh or bincode, found {0}")]
+    UnknownEncoding(String),
+    #[error("Expected Option contains")]
+    ExpectedOptionContains,
+    #[error("Expected Array 'length:' of a non negative integer")]
+    ExpectedArrayLength,
+    #[error("Expected Tlv 'tag:' and 'length_type:' of U8, U16, U32 or U64 and a non negative 'terminator:', found {0}")]
+    ExpectedTlvType(String),
+    #[error("Unknown field {0}, not a top level item of the declaration")]
+    UnknownField(String),
+    #[error("Malformed YAML: {0}")]
+    YamlError(String),
+    #[error("Expected {0} value, found {1}")]
+    UnexpectedValue(String, String),
+    #[error("Missing value for {0}")]
+    MissingValue(String),
+    #[error("Expected {0} bytes of data, found {1}")]
+    InsufficientData(usize, usize),
+    #[error("Failed converting to JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid {0} data: {1}")]
+    InvalidData(String, String),
+}
+
+#[derive(Error, Debug)]
+pub enum SadAccountErrorType {
+    #[error("Failed getting Account from cluster")]
+    FailedAccountGet,
+    #[error("Failed getting Program Accounts from cluster")]
+    FailedProgramAccountGet,
+    #[error("Failed getting Multiple Accounts from cluster")]
+    FailedMultipleAccountGet,
+    #[error("Could not resolve Solana config")]
+    ConfigFileError,
+    #[error("RcpClient creation failed")]
+    RpcSetupFail,
+    #[error("Account key is an executable account")]
+    AccountIsExecutableError,
+    #[error("Not a valid Program key")]
+    NotProgramKeyError,
+    #[error("No declaration registered for accounts owned by {0}")]
+    NoDeclarationError(Pubkey),
+    #[error("Failed getting Transaction from cluster")]
+    FailedTransactionGet,
+    #[error("Failed decoding Transaction")]
+    FailedTransactionDecode,
+    #[error("A program key is required to select instructions for the declaration")]
+    ExpectedProgramKeyError,
+    #[error("Failed subscribing to logs from cluster")]
+    FailedLogsSubscribe,
+    #[error("Account {0} is not an upgradeable loader program, program data or buffer")]
+    NotLoaderAccountError(Pubkey),
+    #[error("A declaration or layout is required to select fields")]
+    ExpectedDeclarationError,
+    #[error("Failed deserializing account {0}: {1}")]
+    DeserializationError(Pubkey, SadTreeError),
+    #[error("Failed deserializing instruction: {0}")]
+    InstructionDeserializationError(SadTreeError),
+    #[error("RPC node has not reached slot {0}")]
+    ContextSlotNotReachedError(Slot),
+}
+
+pub type SadTreeResult<T> = std::result::Result<T, SadTreeError>;
+pub type SadAccountResult<T> = std::result::Result<T, SadAccountErrorType>;
@@ -0,0 +1,319 @@
+//! @brief Instruction queries
+//! Deserializes instruction data, of a transaction's instructions or given
+//! directly, with the instruction declaration selected by program id
+
+use {
+    crate::{
+        desertree::Deseriaizer,
+        errors::{SadAccountErrorType, SadAccountResult, SadTreeError, SadTreeResult},
+        registry::DeclarationSelector,
+        sadtypes::SadValue,
+        solq::{check_context_slot, transaction_commitment},
+    },
+    serde_json::{json, Value},
+    solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig},
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_transaction_status::{
+        option_serializer::OptionSerializer, UiInstruction, UiTransactionEncoding,
+    },
+    std::{rc::Rc, str::FromStr},
+};
+
+/// Account passed to an instruction
+#[derive(Debug, PartialEq)]
+pub struct InstructionAccount {
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl InstructionAccount {
+    pub fn pubkey(&self) -> &Pubkey {
+        &self.pubkey
+    }
+
+    pub fn is_signer(&self) -> bool {
+        self.is_signer
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.is_writable
+    }
+}
+
+/// Instruction with its data deserialized when a declaration matched,
+/// or the error deserializing it when the data does not fit
+#[derive(Debug)]
+pub struct DecodedInstruction {
+    index: String,
+    program_id: Option<Pubkey>,
+    accounts: Vec<InstructionAccount>,
+    data: Vec<u8>,
+    declaration: Option<Rc<Deseriaizer>>,
+    deserialized: Vec<SadValue>,
+    error: Option<SadTreeError>,
+}
+
+impl DecodedInstruction {
+    fn new(
+        index: String,
+        program_id: Option<Pubkey>,
+        accounts: Vec<InstructionAccount>,
+        data: Vec<u8>,
+        selector: &dyn DeclarationSelector,
+    ) -> Self {
+        let (declaration, deserialized, error) =
+            match selector.declaration_for(&program_id.unwrap_or_default(), &data) {
+                Some(d) => match d.deser(&mut data.as_slice()) {
+                    Ok(res) => (Some(d), res, None),
+                    Err(e) => (None, Vec::<SadValue>::new(), Some(e)),
+                },
+                None => (None, Vec::<SadValue>::new(), None),
+            };
+        Self {
+            index,
+            program_id,
+            accounts,
+            data,
+            declaration,
+            deserialized,
+            error,
+        }
+    }
+
+    /// Position in the transaction, "2.1" being the second
+    /// inner instruction of the third instruction
+    pub fn index(&self) -> &String {
+        &self.index
+    }
+
+    pub fn program_id(&self) -> Option<&Pubkey> {
+        self.program_id.as_ref()
+    }
+
+    pub fn accounts(&self) -> &Vec<InstructionAccount> {
+        &self.accounts
+    }
+
+    pub fn is_decoded(&self) -> bool {
+        self.declaration.is_some()
+    }
+
+    /// Error deserializing data that does not fit the declaration
+    pub fn error(&self) -> Option<&SadTreeError> {
+        self.error.as_ref()
+    }
+
+    /// JSON with the account metas and the deserialized data, or the
+    /// base58 raw data, and any error, if it was not deserialized
+    pub fn to_json(&self) -> SadTreeResult<Value> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|a| {
+                json!({
+                    "pubkey": a.pubkey.to_string(),
+                    "is_signer": a.is_signer,
+                    "is_writable": a.is_writable,
+                })
+            })
+            .collect::<Vec<Value>>();
+        let mut jmap = json!({
+            "index": self.index,
+            "program_id": self.program_id.map(|p| p.to_string()),
+            "accounts": accounts,
+        });
+        let jmap_raw = jmap.as_object_mut().unwrap();
+        match &self.declaration {
+            Some(d) => jmap_raw.insert("data".to_string(), d.to_json(&self.deserialized)?),
+            None => jmap_raw.insert(
+                "raw_data".to_string(),
+                json!(bs58::encode(&self.data).into_string()),
+            ),
+        };
+        if let Some(e) = &self.error {
+            jmap_raw.insert("error".to_string(), json!(e.to_string()));
+        }
+        Ok(jmap)
+    }
+}
+
+/// Deserialize instruction data given directly
+pub fn decode_instruction_data(
+    data: Vec<u8>,
+    program_id: Option<Pubkey>,
+    selector: &dyn DeclarationSelector,
+) -> SadAccountResult<DecodedInstruction> {
+    let mut decoded = DecodedInstruction::new(
+        "0".to_string(),
+        program_id,
+        Vec::<InstructionAccount>::new(),
+        data,
+        selector,
+    );
+    match decoded.error.take() {
+        Some(e) => Err(SadAccountErrorType::InstructionDeserializationError(e)),
+        None if decoded.is_decoded() => Ok(decoded),
+        None => Err(SadAccountErrorType::NoDeclarationError(
+            program_id.unwrap_or_default(),
+        )),
+    }
+}
+
+/// Fetches a transaction, from a node that has reached `min_context_slot`,
+/// and deserializes its instructions, inner instructions included,
+/// optionally only those of one program
+pub fn decode_transaction(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    program_id: Option<&Pubkey>,
+    selector: &dyn DeclarationSelector,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<Vec<DecodedInstruction>> {
+    check_context_slot(rpc_client, min_context_slot)?;
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(transaction_commitment(rpc_client)),
+        max_supported_transaction_version: Some(0),
+    };
+    let confirmed = match rpc_client.get_transaction_with_config(signature, config) {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(SadAccountErrorType::FailedTransactionGet);
+        }
+    };
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(SadAccountErrorType::FailedTransactionDecode)?;
+    let message = &transaction.message;
+    let header = message.header();
+    let mut keys = message.static_account_keys().to_vec();
+    let static_count = keys.len();
+    let signed_count = header.num_required_signatures as usize;
+    let signed_writable_count = signed_count - header.num_readonly_signed_accounts as usize;
+    let unsigned_writable_end = static_count - header.num_readonly_unsigned_accounts as usize;
+    let mut loaded_writable_count = 0usize;
+    let mut inner = Vec::<(u8, Vec<UiInstruction>)>::new();
+    if let Some(meta) = &confirmed.transaction.meta {
+        // Accounts loaded from lookup tables follow the static keys
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            loaded_writable_count = loaded.writable.len();
+            for k in loaded.writable.iter().chain(loaded.readonly.iter()) {
+                keys.push(
+                    Pubkey::from_str(k)
+                        .map_err(|_| SadAccountErrorType::FailedTransactionDecode)?,
+                )
+            }
+        }
+        if let OptionSerializer::Some(inner_instructions) = &meta.inner_instructions {
+            for i in inner_instructions {
+                inner.push((i.index, i.instructions.clone()))
+            }
+        }
+    }
+    let account_for = |index: u8| {
+        let i = index as usize;
+        InstructionAccount {
+            pubkey: keys[i],
+            is_signer: i < signed_count,
+            is_writable: if i < static_count {
+                i < signed_writable_count || (i >= signed_count && i < unsigned_writable_end)
+            } else {
+                i - static_count < loaded_writable_count
+            },
+        }
+    };
+    let wanted = |program: &Pubkey| program_id.is_none_or(|p| p == program);
+
+    let mut decoded = Vec::<DecodedInstruction>::new();
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        let program = keys[instruction.program_id_index as usize];
+        if wanted(&program) {
+            decoded.push(DecodedInstruction::new(
+                index.to_string(),
+                Some(program),
+                instruction
+                    .accounts
+                    .iter()
+                    .map(|a| account_for(*a))
+                    .collect(),
+                instruction.data.clone(),
+                selector,
+            ))
+        }
+        for (_, instructions) in inner.iter().filter(|(i, _)| *i as usize == index) {
+            for (inner_index, ui_instruction) in instructions.iter().enumerate() {
+                let compiled = match ui_instruction {
+                    UiInstruction::Compiled(c) => c,
+                    _ => continue,
+                };
+                let program = keys[compiled.program_id_index as usize];
+                if !wanted(&program) {
+                    continue;
+                }
+                let data = bs58::decode(&compiled.data)
+                    .into_vec()
+                    .map_err(|_| SadAccountErrorType::FailedTransactionDecode)?;
+                decoded.push(DecodedInstruction::new(
+                    format!("{}.{}", index, inner_index),
+                    Some(program),
+                    compiled.accounts.iter().map(|a| account_for(*a)).collect(),
+                    data,
+                    selector,
+                ))
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{library::SPL_TOKEN_PROGRAM_ID, registry::SadRegistry};
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_decode_instruction_data_pass() {
+        let registry = SadRegistry::for_instructions(None);
+        let token = Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).unwrap();
+        let mut data = vec![12u8];
+        data.extend(1_000_000u64.try_to_vec().unwrap());
+        data.push(6);
+        let decoded = decode_instruction_data(data, Some(token), &registry).unwrap();
+        assert_eq!(
+            decoded.to_json().unwrap()["data"],
+            json!({"instruction": {"TransferChecked": {"amount": 1_000_000, "decimals": 6}}})
+        );
+    }
+
+    #[test]
+    fn test_decode_instruction_data_fail() {
+        let registry = SadRegistry::for_instructions(None);
+        assert!(decode_instruction_data(vec![3u8], Some(Pubkey::default()), &registry).is_err());
+        // TransferChecked without its amount and decimals
+        let token = Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).unwrap();
+        assert!(matches!(
+            decode_instruction_data(vec![12u8, 1, 2], Some(token), &registry),
+            Err(SadAccountErrorType::InstructionDeserializationError(
+                SadTreeError::InsufficientData(8, 2)
+            ))
+        ));
+        let decoded = DecodedInstruction::new(
+            "1".to_string(),
+            Some(token),
+            Vec::<InstructionAccount>::new(),
+            vec![12u8, 1, 2],
+            &registry,
+        );
+        assert!(!decoded.is_decoded());
+        assert_eq!(decoded.to_json().unwrap()["raw_data"], json!("52rd"));
+        assert_eq!(
+            decoded.to_json().unwrap()["error"],
+            json!("Expected 8 bytes of data, found 2")
+        );
+    }
+}
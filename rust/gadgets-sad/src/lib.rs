@@ -0,0 +1,27 @@
+//! @brief YAML driven deserialization of Solana account, instruction
+//! and event data
+//!
+//! A declaration is parsed into a `Deseriaizer` which turns bytes into
+//! `SadValue`s and those into JSON. Fetching and decoding from a cluster
+//! is enabled with the `rpc` feature
+
+pub mod desertree;
+pub mod errors;
+#[cfg(feature = "rpc")]
+pub mod instq;
+pub mod library;
+#[cfg(feature = "rpc")]
+pub mod loaderq;
+#[cfg(feature = "rpc")]
+pub mod logq;
+pub mod registry;
+pub mod sadtypes;
+#[cfg(feature = "rpc")]
+pub mod solq;
+
+pub use {
+    desertree::{Deseriaizer, SadSchema, SadSchemaElement},
+    errors::{SadAccountErrorType, SadAccountResult, SadTreeError, SadTreeResult},
+    registry::{DeclarationSelector, SadDeclarationSet, SadRegistry},
+    sadtypes::SadValue,
+};
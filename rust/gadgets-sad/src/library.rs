@@ -0,0 +1,293 @@
+//! @brief Bundled library of well known program layouts
+//! Declarations are embedded in the binary so they can be
+//! used by name, with `--layout` or `import:`, without a file on disk
+
+use {
+    lazy_static::*,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashMap, str::FromStr},
+    yaml_rust::{yaml::Yaml, YamlLoader},
+};
+
+/// Well known program ids
+pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const SPL_TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const BPF_UPGRADEABLE_LOADER_ID: &str = "BPFLoaderUpgradeab1e11111111111111111111111";
+pub const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+pub const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+pub const SYSVAR_OWNER_ID: &str = "Sysvar1111111111111111111111111111111111111";
+pub const METAPLEX_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+/// Token-2022 accounts without extensions share the SPL Token layouts
+const SPL_TOKEN_PROGRAMS: &[&str] = &[SPL_TOKEN_PROGRAM_ID, SPL_TOKEN_2022_PROGRAM_ID];
+
+/// Layout name, owning program ids it is registered for and declaration
+/// source of account data
+const LAYOUTS: &[(&str, &[&str], &str)] = &[
+    ("spl_common", &[], include_str!("../layouts/spl_common.yml")),
+    (
+        "spl_token_mint",
+        SPL_TOKEN_PROGRAMS,
+        include_str!("../layouts/spl_token_mint.yml"),
+    ),
+    (
+        "spl_token_account",
+        SPL_TOKEN_PROGRAMS,
+        include_str!("../layouts/spl_token_account.yml"),
+    ),
+    (
+        "spl_token_multisig",
+        SPL_TOKEN_PROGRAMS,
+        include_str!("../layouts/spl_token_multisig.yml"),
+    ),
+    (
+        "token2022_extensions",
+        &[],
+        include_str!("../layouts/token2022_extensions.yml"),
+    ),
+    (
+        "token2022_mint",
+        &[SPL_TOKEN_2022_PROGRAM_ID],
+        include_str!("../layouts/token2022_mint.yml"),
+    ),
+    (
+        "token2022_account",
+        &[SPL_TOKEN_2022_PROGRAM_ID],
+        include_str!("../layouts/token2022_account.yml"),
+    ),
+    (
+        "bpf_upgradeable_loader",
+        &[BPF_UPGRADEABLE_LOADER_ID],
+        include_str!("../layouts/bpf_upgradeable_loader.yml"),
+    ),
+    (
+        "stake_account",
+        &[STAKE_PROGRAM_ID],
+        include_str!("../layouts/stake_account.yml"),
+    ),
+    (
+        "vote_account",
+        &[VOTE_PROGRAM_ID],
+        include_str!("../layouts/vote_account.yml"),
+    ),
+    (
+        "sysvar_clock",
+        &[SYSVAR_OWNER_ID],
+        include_str!("../layouts/sysvar_clock.yml"),
+    ),
+    (
+        "sysvar_rent",
+        &[SYSVAR_OWNER_ID],
+        include_str!("../layouts/sysvar_rent.yml"),
+    ),
+    (
+        "sysvar_epoch_schedule",
+        &[SYSVAR_OWNER_ID],
+        include_str!("../layouts/sysvar_epoch_schedule.yml"),
+    ),
+    (
+        "sysvar_stake_history",
+        &[SYSVAR_OWNER_ID],
+        include_str!("../layouts/sysvar_stake_history.yml"),
+    ),
+    (
+        "sysvar_slot_hashes",
+        &[SYSVAR_OWNER_ID],
+        include_str!("../layouts/sysvar_slot_hashes.yml"),
+    ),
+    (
+        "metaplex_metadata",
+        &[METAPLEX_METADATA_PROGRAM_ID],
+        include_str!("../layouts/metaplex_metadata.yml"),
+    ),
+];
+
+/// Sysvar name, account id and layout name
+const SYSVARS: &[(&str, &str, &str)] = &[
+    (
+        "clock",
+        "SysvarC1ock11111111111111111111111111111111",
+        "sysvar_clock",
+    ),
+    (
+        "rent",
+        "SysvarRent111111111111111111111111111111111",
+        "sysvar_rent",
+    ),
+    (
+        "epoch_schedule",
+        "SysvarEpochSchedu1e111111111111111111111111",
+        "sysvar_epoch_schedule",
+    ),
+    (
+        "stake_history",
+        "SysvarStakeHistory1111111111111111111111111",
+        "sysvar_stake_history",
+    ),
+    (
+        "slot_hashes",
+        "SysvarS1otHashes111111111111111111111111111",
+        "sysvar_slot_hashes",
+    ),
+];
+
+/// Layout name, program id and declaration source of instruction data
+const INSTRUCTION_LAYOUTS: &[(&str, &str, &str)] = &[(
+    "spl_token_instruction",
+    SPL_TOKEN_PROGRAM_ID,
+    include_str!("../layouts/spl_token_instruction.yml"),
+)];
+
+lazy_static! {
+    static ref LAYOUT_MAP: HashMap<&'static str, &'static str> = LAYOUTS
+        .iter()
+        .map(|(n, _, src)| (*n, *src))
+        .chain(INSTRUCTION_LAYOUTS.iter().map(|(n, _, src)| (*n, *src)))
+        .collect();
+    static ref SYSVAR_NAMES: Vec<&'static str> = SYSVARS.iter().map(|(n, _, _)| *n).collect();
+    static ref LAYOUT_NAMES: Vec<&'static str> = LAYOUTS
+        .iter()
+        .map(|(n, _, _)| *n)
+        .chain(INSTRUCTION_LAYOUTS.iter().map(|(n, _, _)| *n))
+        .collect();
+}
+
+/// Names of all bundled layouts
+pub fn layout_names() -> &'static [&'static str] {
+    &LAYOUT_NAMES
+}
+
+/// Names of the bundled layouts registered for a program id,
+/// in the order they should be tried
+pub fn layouts_for_program(program_id: &Pubkey) -> Vec<&'static str> {
+    let pid = program_id.to_string();
+    LAYOUTS
+        .iter()
+        .filter(|(_, p, _)| p.contains(&pid.as_str()))
+        .map(|(n, _, _)| *n)
+        .collect()
+}
+
+/// Names of the bundled instruction layouts registered for a program id
+pub fn instruction_layouts_for_program(program_id: &Pubkey) -> Vec<&'static str> {
+    let pid = program_id.to_string();
+    INSTRUCTION_LAYOUTS
+        .iter()
+        .filter(|(_, p, _)| *p == pid)
+        .map(|(n, _, _)| *n)
+        .collect()
+}
+
+/// Names of the sysvars with bundled layouts
+pub fn sysvar_names() -> &'static [&'static str] {
+    &SYSVAR_NAMES
+}
+
+/// Account id and layout name of a sysvar
+pub fn sysvar(name: &str) -> Option<(Pubkey, &'static str)> {
+    SYSVARS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, id, layout_name)| (Pubkey::from_str(id).unwrap(), *layout_name))
+}
+
+/// Parsed YAML documents of a bundled layout
+pub fn layout(name: &str) -> Option<Vec<Yaml>> {
+    LAYOUT_MAP
+        .get(name)
+        .map(|src| YamlLoader::load_from_str(src).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        desertree::Deseriaizer,
+        registry::{DeclarationSelector, SadRegistry},
+    };
+    use solana_sdk::{
+        account::Account,
+        sysvar::{self, clock::Clock, rent::Rent},
+    };
+
+    #[test]
+    fn test_layouts_load_pass() {
+        for name in layout_names() {
+            let docs = layout(name).unwrap();
+            let desc = Deseriaizer::new(&docs[0]);
+            println!("{} {:?}", name, desc.schema().item_type_prefixes());
+        }
+    }
+
+    #[test]
+    fn test_layouts_for_program_pass() {
+        let token = Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).unwrap();
+        assert_eq!(
+            layouts_for_program(&token),
+            vec!["spl_token_mint", "spl_token_account", "spl_token_multisig"]
+        );
+        let token_2022 = Pubkey::from_str(SPL_TOKEN_2022_PROGRAM_ID).unwrap();
+        assert_eq!(
+            layouts_for_program(&token_2022),
+            vec![
+                "spl_token_mint",
+                "spl_token_account",
+                "spl_token_multisig",
+                "token2022_mint",
+                "token2022_account"
+            ]
+        );
+        assert!(layouts_for_program(&Pubkey::default()).is_empty());
+        assert_eq!(
+            instruction_layouts_for_program(&token),
+            vec!["spl_token_instruction"]
+        );
+    }
+
+    #[test]
+    fn test_sysvars_pass() {
+        assert_eq!(sysvar("clock").unwrap().0, sysvar::clock::id());
+        assert_eq!(sysvar("rent").unwrap().0, sysvar::rent::id());
+        assert_eq!(
+            sysvar("epoch_schedule").unwrap().0,
+            sysvar::epoch_schedule::id()
+        );
+        assert_eq!(
+            sysvar("stake_history").unwrap().0,
+            sysvar::stake_history::id()
+        );
+        assert_eq!(sysvar("slot_hashes").unwrap().0, sysvar::slot_hashes::id());
+        assert!(sysvar("fees").is_none());
+
+        // Sysvars are also selected by their owner and data size
+        let registry = SadRegistry::new(None);
+        let owner = Pubkey::from_str(SYSVAR_OWNER_ID).unwrap();
+        let clock = Clock {
+            slot: 7,
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        let mut account = Account::new(0, 40, &owner);
+        account.serialize_data(&clock).unwrap();
+        let data = account.data;
+        let desc = registry.declaration_for(&owner, &data).unwrap();
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["slot"], 7);
+        assert_eq!(json_out["unix_timestamp"], 1_700_000_000);
+        let mut account = Account::new(0, 17, &owner);
+        account.serialize_data(&Rent::default()).unwrap();
+        let data = account.data;
+        let desc = registry.declaration_for(&owner, &data).unwrap();
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["exemption_threshold"], 2.0);
+    }
+
+    #[test]
+    fn test_layout_unknown_fail() {
+        assert!(layout("not_a_layout").is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! @brief Upgradeable loader queries
+//! Finds the ELF of a program from its Program, ProgramData or
+//! Buffer account and writes it to a file
+
+use {
+    crate::{
+        errors::{SadAccountErrorType, SadAccountResult},
+        solq::solana_account_slice,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        account::Account,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        clock::Slot,
+        hash::hash,
+        pubkey::Pubkey,
+    },
+    std::{fs, io},
+};
+
+/// True for the accounts of the upgradeable loader, which hold
+/// loader state rather than being opaque program data
+pub fn is_loader_account(account: &Account) -> bool {
+    account.owner == bpf_loader_upgradeable::id()
+}
+
+/// ELF of the program of a Program, ProgramData or Buffer account.
+/// A Program account's ELF is read from its ProgramData account, from
+/// a node that has reached `min_context_slot`
+pub fn program_elf(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    account: &Account,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<Vec<u8>> {
+    if !is_loader_account(account) {
+        return Err(SadAccountErrorType::NotLoaderAccountError(*key));
+    }
+    match account.deserialize_data::<UpgradeableLoaderState>() {
+        Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) => {
+            let (_, programdata) =
+                solana_account_slice(rpc_client, &programdata_address, None, min_context_slot)?;
+            program_elf(
+                rpc_client,
+                &programdata_address,
+                &programdata,
+                min_context_slot,
+            )
+        }
+        Ok(UpgradeableLoaderState::ProgramData { .. }) => {
+            Ok(account.data[UpgradeableLoaderState::size_of_programdata_metadata()..].to_vec())
+        }
+        Ok(UpgradeableLoaderState::Buffer { .. }) => {
+            Ok(account.data[UpgradeableLoaderState::size_of_buffer_metadata()..].to_vec())
+        }
+        _ => Err(SadAccountErrorType::NotLoaderAccountError(*key)),
+    }
+}
+
+/// Sha256 hash of data as hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    hash(data)
+        .to_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Writes the program bytes to a file as deployed, including the zero
+/// padding that follows the ELF in the account, and returns their sha256
+/// hash as hex
+pub fn write_elf(file_name: &str, elf: &[u8]) -> io::Result<String> {
+    fs::write(file_name, elf)?;
+    Ok(sha256_hex(elf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        library::BPF_UPGRADEABLE_LOADER_ID,
+        registry::{DeclarationSelector, SadRegistry},
+    };
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_loader_state_pass() {
+        let registry = SadRegistry::new(None);
+        let loader = Pubkey::from_str(BPF_UPGRADEABLE_LOADER_ID).unwrap();
+        let authority = Pubkey::new_unique();
+        let mut data = bincode_state(&UpgradeableLoaderState::ProgramData {
+            slot: 42,
+            upgrade_authority_address: Some(authority),
+        });
+        data.extend([0x7f, b'E', b'L', b'F', 0, 0]);
+        let desc = registry.declaration_for(&loader, &data).unwrap();
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(
+            json_out["state"],
+            json!({"ProgramData": {"slot": 42, "upgrade_authority_address": authority.to_string()}})
+        );
+        let data = bincode_state(&UpgradeableLoaderState::Buffer {
+            authority_address: None,
+        });
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(
+            json_out["state"],
+            json!({"Buffer": {"authority_address": null}})
+        );
+    }
+
+    #[test]
+    fn test_write_elf_pass() {
+        let elf = [0x7fu8, b'E', b'L', b'F', 1, 0, 0, 0];
+        let file_name = std::env::temp_dir().join("sad_test_write_elf.so");
+        let file_name = file_name.to_str().unwrap();
+        let elf_hash = write_elf(file_name, &elf).unwrap();
+        assert_eq!(fs::read(file_name).unwrap(), elf.to_vec());
+        assert_eq!(
+            elf_hash,
+            "2db42aeb15b2890871ea157ca6e3d2828b930df3c12865596a0b4472afff2ed3"
+        );
+        fs::remove_file(file_name).unwrap();
+    }
+
+    /// Serialized state, as the loader's state is bincode
+    fn bincode_state(state: &UpgradeableLoaderState) -> Vec<u8> {
+        let mut account = Account::new(0, 45, &bpf_loader_upgradeable::id());
+        account.serialize_data(state).unwrap();
+        let size = match state {
+            UpgradeableLoaderState::Buffer { .. } => {
+                UpgradeableLoaderState::size_of_buffer_metadata()
+            }
+            _ => UpgradeableLoaderState::size_of_programdata_metadata(),
+        };
+        account.data[..size].to_vec()
+    }
+}
@@ -0,0 +1,221 @@
+//! @brief Log queries
+//! Finds the `Program data:` payloads in program logs, of a transaction,
+//! a log subscription or a saved log file, and deserializes them as events
+
+use {
+    crate::{
+        errors::{SadAccountErrorType, SadAccountResult},
+        registry::{DeclarationSelector, EVENT_DISCRIMINATOR_LEN},
+        solq::{check_context_slot, transaction_commitment, EventResultContext},
+    },
+    solana_client::{
+        pubsub_client::{LogsSubscription, PubsubClient},
+        rpc_client::RpcClient,
+        rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    },
+    solana_sdk::{
+        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    },
+    solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding},
+    std::{fs::read_to_string, io, str::FromStr},
+};
+
+const LOG_PROGRAM: &str = "Program ";
+const LOG_INVOKE: &str = " invoke [";
+const LOG_SUCCESS: &str = " success";
+const LOG_FAILED: &str = " failed";
+const LOG_PROGRAM_DATA: &str = "Program data: ";
+
+/// Data logged by programs, each with the program that logged it.
+/// The invoke/success/failed lines track which program is executing
+pub fn program_data(logs: &[String]) -> Vec<(Pubkey, Vec<u8>)> {
+    let mut invoked = Vec::<Pubkey>::new();
+    let mut data = Vec::<(Pubkey, Vec<u8>)>::new();
+    for line in logs {
+        let line = line.trim();
+        if let Some(payload) = line.strip_prefix(LOG_PROGRAM_DATA) {
+            if let Some(program) = invoked.last() {
+                // Each logged slice is base64 encoded, separated by a space
+                let bytes = payload
+                    .split_whitespace()
+                    .map(base64::decode)
+                    .collect::<Result<Vec<Vec<u8>>, _>>();
+                if let Ok(bytes) = bytes {
+                    data.push((*program, bytes.concat()))
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix(LOG_PROGRAM) {
+            let program = rest
+                .split_whitespace()
+                .next()
+                .and_then(|p| Pubkey::from_str(p).ok());
+            if let Some(program) = program {
+                if rest.contains(LOG_INVOKE) {
+                    invoked.push(program)
+                } else if rest.ends_with(LOG_SUCCESS) || rest.contains(LOG_FAILED) {
+                    invoked.pop();
+                }
+            }
+        }
+    }
+    data
+}
+
+/// Deserializes the events in program logs, optionally only those of
+/// one program. The event data following the discriminator is deserialized
+/// with the declaration selected by program id and discriminator. Events
+/// carry the signature and slot the logs are from, when known
+pub fn decode_events(
+    signature: Option<Signature>,
+    context_slot: Option<Slot>,
+    logs: &[String],
+    program_id: Option<&Pubkey>,
+    selector: &dyn DeclarationSelector,
+) -> Vec<EventResultContext> {
+    let mut events = Vec::<EventResultContext>::new();
+    for (program, data) in program_data(logs) {
+        if program_id.is_some_and(|p| *p != program) || data.len() < EVENT_DISCRIMINATOR_LEN {
+            continue;
+        }
+        match selector.declaration_for(&program, &data) {
+            Some(destree) => match destree.deser(&mut &data[EVENT_DISCRIMINATOR_LEN..]) {
+                Ok(res) => events.push(EventResultContext::new(
+                    signature,
+                    context_slot,
+                    program,
+                    res,
+                    destree,
+                )),
+                Err(e) => eprintln!("Event of {} failed deserializing: {}", program, e),
+            },
+            None => eprintln!("No declaration matches event of {}, skipping", program),
+        }
+    }
+    events
+}
+
+/// Retrieves the log messages of a transaction, and the slot of the
+/// transaction, from a node that has reached `min_context_slot`
+pub fn transaction_logs(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<(Slot, Vec<String>)> {
+    check_context_slot(rpc_client, min_context_slot)?;
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(transaction_commitment(rpc_client)),
+        max_supported_transaction_version: Some(0),
+    };
+    let confirmed = match rpc_client.get_transaction_with_config(signature, config) {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(SadAccountErrorType::FailedTransactionGet);
+        }
+    };
+    match confirmed.transaction.meta.map(|m| m.log_messages) {
+        Some(OptionSerializer::Some(logs)) => Ok((confirmed.slot, logs)),
+        _ => Ok((confirmed.slot, Vec::<String>::new())),
+    }
+}
+
+/// Reads log lines saved to a file (e.g. from `solana logs`)
+pub fn file_logs(file_name: &str) -> io::Result<Vec<String>> {
+    Ok(read_to_string(file_name)?
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// Subscribes to the logs of transactions mentioning a program,
+/// or of all transactions, at the given commitment
+pub fn subscribe_logs(
+    websocket_url: &str,
+    program_id: Option<&Pubkey>,
+    commitment: CommitmentConfig,
+) -> SadAccountResult<LogsSubscription> {
+    let filter = match program_id {
+        Some(p) => RpcTransactionLogsFilter::Mentions(vec![p.to_string()]),
+        None => RpcTransactionLogsFilter::All,
+    };
+    let config = RpcTransactionLogsConfig {
+        commitment: Some(commitment),
+    };
+    PubsubClient::logs_subscribe(websocket_url, filter, config).map_err(|e| {
+        eprintln!("{}", e);
+        SadAccountErrorType::FailedLogsSubscribe
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{event_discriminator, SadDeclarationSet};
+    use borsh::BorshSerialize;
+    use yaml_rust::YamlLoader;
+
+    const EVENT_DECL: &str = "match:\n  event: Deposited\ndeposited:\n  - amount:\n      type: U64\n  - depositor:\n      type: PublicKey\n";
+
+    fn deposited_logs(program: &Pubkey, depositor: &Pubkey) -> Vec<String> {
+        let mut data = event_discriminator("Deposited").to_vec();
+        data.extend(500u64.try_to_vec().unwrap());
+        data.extend(depositor.to_bytes());
+        let other = Pubkey::new_unique();
+        vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: Deposit".to_string(),
+            format!("Program {} invoke [2]", other),
+            format!("Program data: {}", base64::encode([9u8; 12])),
+            format!("Program {} success", other),
+            format!("Program data: {}", base64::encode(&data)),
+            format!("Program {} consumed 5000 of 200000 compute units", program),
+            format!("Program {} success", program),
+        ]
+    }
+
+    #[test]
+    fn test_program_data_pass() {
+        let program = Pubkey::new_unique();
+        let logs = deposited_logs(&program, &Pubkey::new_unique());
+        let data = program_data(&logs);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].1, vec![9u8; 12]);
+        assert_eq!(data[1].0, program);
+    }
+
+    #[test]
+    fn test_decode_events_pass() {
+        let program = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let logs = deposited_logs(&program, &depositor);
+        let selector = SadDeclarationSet::new(YamlLoader::load_from_str(EVENT_DECL).unwrap());
+        let events = decode_events(None, Some(42), &logs, Some(&program), &selector);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name(), "deposited");
+        assert_eq!(events[0].context_slot(), Some(42));
+        assert_eq!(events[0].to_json().unwrap()["amount"], 500);
+        assert_eq!(
+            events[0].to_json().unwrap()["depositor"],
+            depositor.to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn test_decode_events_truncated_fail() {
+        let program = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let mut logs = deposited_logs(&program, &depositor);
+        // A Deposited event missing the depositor is reported and skipped
+        let mut data = event_discriminator("Deposited").to_vec();
+        data.extend(7u64.try_to_vec().unwrap());
+        logs.insert(
+            5,
+            format!("Program data: {}", base64::encode(&data[..data.len() - 1])),
+        );
+        let selector = SadDeclarationSet::new(YamlLoader::load_from_str(EVENT_DECL).unwrap());
+        let events = decode_events(None, None, &logs, Some(&program), &selector);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_json().unwrap()["amount"], 500);
+    }
+}
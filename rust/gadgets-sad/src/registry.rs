@@ -0,0 +1,429 @@
+//! @brief Declaration registry
+//! Selects the declaration for an account by its owning program id,
+//! first from `<program id>.yml` files in a registry directory and
+//! then from the bundled layouts. Instruction declarations are selected
+//! the same way by program id from `<program id>.instruction.yml` files
+//! and event declarations from `<program id>.event.yml` files
+
+use {
+    crate::{
+        desertree::Deseriaizer,
+        library::{instruction_layouts_for_program, layout, layouts_for_program},
+    },
+    gadgets_common::load_yaml_file,
+    serde_json::{json, Value},
+    solana_sdk::{hash::hashv, pubkey::Pubkey},
+    std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc},
+    yaml_rust::yaml::Yaml,
+};
+
+const SAD_YAML_MATCH: &str = "match";
+const SAD_MATCH_DATA_SIZE: &str = "data_size";
+const SAD_MATCH_MIN_DATA_SIZE: &str = "min_data_size";
+const SAD_MATCH_OFFSET: &str = "offset";
+const SAD_MATCH_BYTES: &str = "bytes";
+const SAD_MATCH_EVENT: &str = "event";
+
+/// Length of the Anchor event discriminator prefixing event data
+pub const EVENT_DISCRIMINATOR_LEN: usize = 8;
+
+/// Chooses the declaration used to deserialize an account's data
+pub trait DeclarationSelector {
+    fn declaration_for(&self, owner: &Pubkey, data: &[u8]) -> Option<Rc<Deseriaizer>>;
+    /// Bytes of account data to fetch, None for all of it. Selection
+    /// by data needs all of it
+    fn data_len(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// A single declaration is used for every account
+impl DeclarationSelector for Rc<Deseriaizer> {
+    fn declaration_for(&self, _owner: &Pubkey, _data: &[u8]) -> Option<Rc<Deseriaizer>> {
+        Some(self.clone())
+    }
+
+    fn data_len(&self) -> Option<usize> {
+        Deseriaizer::data_len(self)
+    }
+}
+
+/// Registered declaration with its optional 'match:' criteria
+#[derive(Debug)]
+struct SadRegistryEntry {
+    criteria: Yaml,
+    declaration: Rc<Deseriaizer>,
+}
+
+/// What the registered declarations deserialize
+#[derive(Debug, Default, PartialEq)]
+enum SadRegistryKind {
+    #[default]
+    Account,
+    Instruction,
+    Event,
+}
+
+/// Declarations keyed by owning program id
+#[derive(Debug, Default)]
+pub struct SadRegistry {
+    directory: Option<PathBuf>,
+    kind: SadRegistryKind,
+    entries: RefCell<HashMap<Pubkey, Rc<Vec<SadRegistryEntry>>>>,
+}
+
+impl SadRegistry {
+    pub fn new(directory: Option<&str>) -> Self {
+        Self {
+            directory: directory.map(PathBuf::from),
+            ..SadRegistry::default()
+        }
+    }
+
+    /// Registry of instruction declarations keyed by program id
+    pub fn for_instructions(directory: Option<&str>) -> Self {
+        Self {
+            directory: directory.map(PathBuf::from),
+            kind: SadRegistryKind::Instruction,
+            ..SadRegistry::default()
+        }
+    }
+
+    /// Registry of event declarations keyed by program id
+    pub fn for_events(directory: Option<&str>) -> Self {
+        Self {
+            directory: directory.map(PathBuf::from),
+            kind: SadRegistryKind::Event,
+            ..SadRegistry::default()
+        }
+    }
+
+    /// Loads, once per program id, the registered declarations
+    fn entries_for(&self, owner: &Pubkey) -> Rc<Vec<SadRegistryEntry>> {
+        if let Some(entries) = self.entries.borrow().get(owner) {
+            return entries.clone();
+        }
+        let mut docs = Vec::<Yaml>::new();
+        let mut source = "bundled layouts".to_string();
+        if let Some(dir) = &self.directory {
+            let path = match self.kind {
+                SadRegistryKind::Account => dir.join(format!("{}.yml", owner)),
+                SadRegistryKind::Instruction => dir.join(format!("{}.instruction.yml", owner)),
+                SadRegistryKind::Event => dir.join(format!("{}.event.yml", owner)),
+            };
+            if path.exists() {
+                match load_yaml_file(path.to_str().unwrap()) {
+                    Ok(file_docs) => {
+                        source = path.display().to_string();
+                        docs.extend(file_docs)
+                    }
+                    Err(e) => eprintln!("File error: On {} {}", path.display(), e),
+                }
+            }
+        }
+        let bundled = match self.kind {
+            SadRegistryKind::Account => layouts_for_program(owner),
+            SadRegistryKind::Instruction => instruction_layouts_for_program(owner),
+            SadRegistryKind::Event => Vec::<&str>::new(),
+        };
+        for name in bundled {
+            docs.extend(layout(name).unwrap())
+        }
+        let entries = Rc::new(registry_entries(docs, &source));
+        self.entries.borrow_mut().insert(*owner, entries.clone());
+        entries
+    }
+}
+
+impl DeclarationSelector for SadRegistry {
+    /// First registered declaration whose 'match:' criteria the data satisfies
+    fn declaration_for(&self, owner: &Pubkey, data: &[u8]) -> Option<Rc<Deseriaizer>> {
+        self.entries_for(owner)
+            .iter()
+            .find(|e| matches_data(&e.criteria, data))
+            .map(|e| e.declaration.clone())
+    }
+}
+
+/// Declarations of the documents, in order, with their 'match:' criteria.
+/// Documents that are not valid declarations are reported and skipped
+fn registry_entries(docs: Vec<Yaml>, source: &str) -> Vec<SadRegistryEntry> {
+    docs.into_iter()
+        .enumerate()
+        .filter_map(|(index, doc)| match Deseriaizer::try_new(&doc) {
+            Ok(declaration) => Some(SadRegistryEntry {
+                criteria: doc[SAD_YAML_MATCH].clone(),
+                declaration: Rc::new(declaration),
+            }),
+            Err(e) => {
+                eprintln!("Declaration error: On {} document {} {}", source, index, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Declarations of a declaration file selected by 'match:' criteria
+/// alone, regardless of owner
+#[derive(Debug)]
+pub struct SadDeclarationSet {
+    entries: Vec<SadRegistryEntry>,
+}
+
+impl SadDeclarationSet {
+    pub fn new(docs: Vec<Yaml>) -> Self {
+        Self {
+            entries: registry_entries(docs, "declaration file"),
+        }
+    }
+}
+
+impl DeclarationSelector for SadDeclarationSet {
+    fn declaration_for(&self, _owner: &Pubkey, data: &[u8]) -> Option<Rc<Deseriaizer>> {
+        self.entries
+            .iter()
+            .find(|e| matches_data(&e.criteria, data))
+            .map(|e| e.declaration.clone())
+    }
+}
+
+/// Anchor event discriminator, the first 8 bytes of sha256("event:<name>")
+pub fn event_discriminator(name: &str) -> [u8; EVENT_DISCRIMINATOR_LEN] {
+    let mut discriminator = [0u8; EVENT_DISCRIMINATOR_LEN];
+    discriminator.copy_from_slice(
+        &hashv(&[b"event:", name.as_bytes()]).to_bytes()[..EVENT_DISCRIMINATOR_LEN],
+    );
+    discriminator
+}
+
+/// JSON Schema of a declaration's 'match:' criteria
+pub(crate) fn match_json_schema() -> Value {
+    let size = json!({"type": "integer", "minimum": 0});
+    json!({
+        "type": "object",
+        "properties": {
+            SAD_MATCH_DATA_SIZE: size,
+            SAD_MATCH_MIN_DATA_SIZE: size,
+            SAD_MATCH_OFFSET: size,
+            SAD_MATCH_BYTES: {
+                "type": "array",
+                "items": {"type": "integer", "minimum": 0, "maximum": 255},
+            },
+            SAD_MATCH_EVENT: {"type": "string"},
+        },
+        "additionalProperties": false,
+    })
+}
+
+/// Checks account data against a declaration's 'match:' criteria,
+/// a declaration without criteria matches any data
+fn matches_data(criteria: &Yaml, data: &[u8]) -> bool {
+    if criteria.is_badvalue() {
+        return true;
+    }
+    if let Some(size) = criteria[SAD_MATCH_DATA_SIZE].as_i64() {
+        if data.len() as i64 != size {
+            return false;
+        }
+    }
+    if let Some(size) = criteria[SAD_MATCH_MIN_DATA_SIZE].as_i64() {
+        if (data.len() as i64) < size {
+            return false;
+        }
+    }
+    if let Some(name) = criteria[SAD_MATCH_EVENT].as_str() {
+        if !data.starts_with(&event_discriminator(name)) {
+            return false;
+        }
+    }
+    if let Some(bytes) = criteria[SAD_MATCH_BYTES].as_vec() {
+        let offset = criteria[SAD_MATCH_OFFSET].as_i64().unwrap_or(0) as usize;
+        if data.len() < offset + bytes.len() {
+            return false;
+        }
+        for (i, b) in bytes.iter().enumerate() {
+            if b.as_i64() != Some(data[offset + i] as i64) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::{SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, STAKE_PROGRAM_ID};
+    use borsh::BorshSerialize;
+    use serde_json::json;
+    use std::{fs, str::FromStr};
+    use yaml_rust::YamlLoader;
+
+    /// vscode changes cwd depending on running test or debugging test
+    fn get_sample_registry() -> SadRegistry {
+        if std::env::current_dir().unwrap().ends_with("gadgets-sad") {
+            SadRegistry::new(Some("../../samples/yamldecls"))
+        } else {
+            SadRegistry::new(Some("../samples/yamldecls"))
+        }
+    }
+
+    #[test]
+    fn test_match_criteria_pass() {
+        let docs = YamlLoader::load_from_str(
+            "data_size: 8\n---\nmin_data_size: 4\noffset: 2\nbytes: [1, 2]\n",
+        )
+        .unwrap();
+        assert!(matches_data(&Yaml::BadValue, &[]));
+        assert!(matches_data(&docs[0], &[0u8; 8]));
+        assert!(!matches_data(&docs[0], &[0u8; 9]));
+        assert!(matches_data(&docs[1], &[0, 0, 1, 2]));
+        assert!(!matches_data(&docs[1], &[0, 0, 1, 3]));
+        assert!(!matches_data(&docs[1], &[0, 0, 1]));
+    }
+
+    #[test]
+    fn test_match_event_pass() {
+        assert_eq!(
+            event_discriminator("Deposited"),
+            [111, 141, 26, 45, 161, 35, 100, 57]
+        );
+        let docs = YamlLoader::load_from_str("event: Deposited\n").unwrap();
+        let mut data = event_discriminator("Deposited").to_vec();
+        data.extend([1u8, 2, 3]);
+        assert!(matches_data(&docs[0], &data));
+        assert!(!matches_data(&docs[0], &event_discriminator("Withdrawn")));
+        assert!(!matches_data(&docs[0], &data[..4]));
+    }
+
+    #[test]
+    fn test_registry_directory_pass() {
+        let registry = get_sample_registry();
+        let owner = Pubkey::from_str("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv").unwrap();
+        assert!(registry.declaration_for(&owner, &[0u8; 16]).is_some());
+        assert!(registry
+            .declaration_for(&Pubkey::default(), &[0u8; 16])
+            .is_none());
+    }
+
+    #[test]
+    fn test_registry_bad_declaration_fail() {
+        let dir = std::env::temp_dir().join("sad_test_registry_bad_declaration");
+        fs::create_dir_all(&dir).unwrap();
+        let owner = Pubkey::new_unique();
+        fs::write(
+            dir.join(format!("{}.yml", owner)),
+            "bad:\n  - a:\n      type: Array\n      contains:\n        - type: U8\n---\ngood:\n  - count:\n      type: U32\n",
+        )
+        .unwrap();
+        let registry = SadRegistry::new(dir.to_str());
+        let declaration = registry.declaration_for(&owner, &[0u8; 4]).unwrap();
+        assert_eq!(
+            declaration.deser_to_json(&7u32.to_le_bytes()).unwrap(),
+            json!({"count": 7})
+        );
+        assert_eq!(registry.entries_for(&owner).len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_registry_bundled_pass() {
+        let registry = SadRegistry::new(None);
+        let owner = Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).unwrap();
+        let mint = registry.declaration_for(&owner, &[0u8; 82]).unwrap();
+        let account = registry.declaration_for(&owner, &[0u8; 165]).unwrap();
+        assert_eq!(mint.tree().get_name(), "spl_token_mint");
+        assert_eq!(account.tree().get_name(), "spl_token_account");
+        assert!(registry.declaration_for(&owner, &[0u8; 100]).is_none());
+        let instructions = SadRegistry::for_instructions(None);
+        let transfer = instructions.declaration_for(&owner, &[3u8]).unwrap();
+        assert_eq!(transfer.tree().get_name(), "spl_token_instruction");
+    }
+
+    #[test]
+    fn test_registry_token_2022_pass() {
+        let registry = SadRegistry::new(None);
+        let owner = Pubkey::from_str(SPL_TOKEN_2022_PROGRAM_ID).unwrap();
+        let authority = Pubkey::new_unique();
+        let metadata = Pubkey::new_unique();
+        let mut data = vec![0u8; 165];
+        data[44] = 6;
+        data[45] = 1;
+        data.push(1);
+        // MetadataPointer followed by an undeclared extension
+        data.extend(18u16.try_to_vec().unwrap());
+        data.extend(64u16.try_to_vec().unwrap());
+        data.extend(authority.to_bytes());
+        data.extend(metadata.to_bytes());
+        data.extend(5u16.try_to_vec().unwrap());
+        data.extend(2u16.try_to_vec().unwrap());
+        data.extend([0u8; 2]);
+        let mint = registry.declaration_for(&owner, &data).unwrap();
+        assert_eq!(mint.tree().get_name(), "token2022_mint");
+        let json_out = mint
+            .to_json(&mint.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["mint"]["decimals"], 6);
+        assert_eq!(json_out["account_type"], 1);
+        assert_eq!(
+            json_out["extensions"],
+            json!([
+                {"MetadataPointer": {
+                    "authority": authority.to_string(),
+                    "metadata_address": metadata.to_string(),
+                }},
+                {"unknown_variant": 5},
+            ])
+        );
+
+        let mut data = vec![0u8; 165];
+        data.push(2);
+        data.extend(7u16.try_to_vec().unwrap());
+        data.extend(0u16.try_to_vec().unwrap());
+        let account = registry.declaration_for(&owner, &data).unwrap();
+        assert_eq!(account.tree().get_name(), "token2022_account");
+        let json_out = account
+            .to_json(&account.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out["extensions"], json!(["ImmutableOwner"]));
+        let multisig = registry.declaration_for(&owner, &[0u8; 355]).unwrap();
+        assert_eq!(multisig.tree().get_name(), "spl_token_multisig");
+    }
+
+    #[test]
+    fn test_registry_stake_pass() {
+        let registry = SadRegistry::new(None);
+        let owner = Pubkey::from_str(STAKE_PROGRAM_ID).unwrap();
+        let (staker, voter) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Bincode StakeStateV2::Initialized, the remainder being zero
+        let mut data = 1u32.try_to_vec().unwrap();
+        data.extend(2_282_880u64.try_to_vec().unwrap());
+        data.extend(staker.to_bytes());
+        data.extend(staker.to_bytes());
+        data.resize(200, 0);
+        let stake = registry.declaration_for(&owner, &data).unwrap();
+        assert_eq!(stake.tree().get_name(), "stake_account");
+        let json_out = stake
+            .to_json(&stake.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        let meta = &json_out["state"]["Initialized"]["meta"];
+        assert_eq!(meta["rent_exempt_reserve"], 2_282_880);
+        assert_eq!(
+            meta["authorized"]["withdrawer"],
+            staker.to_string().as_str()
+        );
+        assert_eq!(meta["lockup"]["epoch"], 0);
+
+        let mut data = 2u32.try_to_vec().unwrap();
+        data.resize(124, 0);
+        data.extend(voter.to_bytes());
+        data.extend(5_000_000u64.try_to_vec().unwrap());
+        data.resize(200, 0);
+        let json_out = stake
+            .to_json(&stake.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        let delegation = &json_out["state"]["Stake"]["stake"]["delegation"];
+        assert_eq!(delegation["voter_pubkey"], voter.to_string().as_str());
+        assert_eq!(delegation["stake"], 5_000_000);
+    }
+}
@@ -0,0 +1,457 @@
+//! @brief Deserialization Support
+
+use solana_sdk::pubkey::Pubkey;
+
+use {
+    crate::errors::{SadTreeError, SadTreeResult},
+    borsh::BorshDeserialize,
+    lazy_static::*,
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::{collections::HashMap, str::FromStr},
+    strum::{EnumIter, EnumProperty, EnumVariantNames, VariantNames},
+};
+
+/// Serializes as the variant name tagging the value. U128, I128 and
+/// PublicKey values are strings so that any format can hold them
+#[derive(
+    Clone, Debug, PartialEq, EnumIter, EnumVariantNames, EnumProperty, Serialize, Deserialize,
+)]
+pub enum SadValue {
+    #[strum(props(Type = "Scalar"))]
+    String(String),
+    #[strum(props(Type = "Scalar"))]
+    Bool(bool),
+    #[strum(props(Type = "Scalar"))]
+    U8(u8),
+    #[strum(props(Type = "Scalar"))]
+    U16(u16),
+    #[strum(props(Type = "Scalar"))]
+    U32(u32),
+    #[strum(props(Type = "Scalar"))]
+    U64(u64),
+    #[strum(props(Type = "Scalar"))]
+    U128(#[serde(with = "display_string")] u128),
+    #[strum(props(Type = "Scalar"))]
+    I8(i8),
+    #[strum(props(Type = "Scalar"))]
+    I16(i16),
+    #[strum(props(Type = "Scalar"))]
+    I32(i32),
+    #[strum(props(Type = "Scalar"))]
+    I64(i64),
+    #[strum(props(Type = "Scalar"))]
+    I128(#[serde(with = "display_string")] i128),
+    #[strum(props(Type = "Scalar"))]
+    F32(f32),
+    #[strum(props(Type = "Scalar"))]
+    F64(f64),
+    #[strum(props(Type = "Compound"))]
+    Vec(Vec<SadValue>),
+    #[strum(props(Type = "Compound"))]
+    Tuple(Vec<SadValue>),
+    #[strum(props(Type = "Compound"))]
+    Option(Vec<SadValue>),
+    #[strum(props(Type = "Nested Compound"))]
+    HashMap(Vec<Vec<SadValue>>),
+    #[strum(props(Type = "Compound"))]
+    CStruct(Vec<SadValue>),
+    #[strum(props(Type = "Compound"))]
+    NamedField(Vec<SadValue>),
+    #[strum(props(Type = "Scalar"))]
+    PublicKey(#[serde(with = "display_string")] Pubkey),
+    #[strum(props(Type = "Compound"))]
+    Enum(Vec<SadValue>),
+    #[strum(props(Type = "Compound"))]
+    Tlv(Vec<SadValue>),
+}
+
+pub fn is_sadvalue_type(in_str: &str) -> bool {
+    match SadValue::VARIANTS.iter().position(|&r| r == in_str) {
+        Some(_) => true,
+        None => false,
+    }
+}
+
+pub fn is_sadtype_scalar(intype: &SadValue) -> bool {
+    // First check if it's a valid type
+    let s: &'static str = intype.get_str("Type").unwrap();
+    if s == "Scalar" {
+        true
+    } else {
+        false
+    }
+}
+
+pub fn is_simple_compound(intype: &SadValue) -> bool {
+    // First check if it's a valid type
+    let s: &'static str = intype.get_str("Type").unwrap();
+    if s == "Compound" {
+        true
+    } else {
+        false
+    }
+}
+
+pub fn from_scalar_value_for(intype: &SadValue) -> String {
+    match intype {
+        SadValue::String(item) => item.clone(),
+        SadValue::Bool(item) => item.to_string(),
+        SadValue::U8(item) => item.to_string(),
+        SadValue::U16(item) => item.to_string(),
+        SadValue::U32(item) => item.to_string(),
+        SadValue::U64(item) => item.to_string(),
+        SadValue::U128(item) => item.to_string(),
+        SadValue::I8(item) => item.to_string(),
+        SadValue::I16(item) => item.to_string(),
+        SadValue::I32(item) => item.to_string(),
+        SadValue::I64(item) => item.to_string(),
+        SadValue::I128(item) => item.to_string(),
+        SadValue::F32(item) => item.to_string(),
+        SadValue::F64(item) => item.to_string(),
+        SadValue::PublicKey(item) => item.to_string(),
+        _ => unreachable!(),
+    }
+}
+
+/// Serde of values as their Display string
+mod display_string {
+    use {
+        serde::{de, Deserialize, Deserializer, Serializer},
+        std::{fmt::Display, str::FromStr},
+    };
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Scalar value of a type from its JSON, as rendered by a declaration,
+/// None when the JSON does not hold a value of the type
+pub fn from_json_value_for(key: &str, value: &Value) -> Option<SadValue> {
+    // U128, I128 and PublicKey render as strings
+    let text = value.as_str();
+    Some(match key {
+        "String" => SadValue::String(text?.to_string()),
+        "Bool" => SadValue::Bool(value.as_bool()?),
+        "U8" => SadValue::U8(value.as_u64()?.try_into().ok()?),
+        "U16" => SadValue::U16(value.as_u64()?.try_into().ok()?),
+        "U32" => SadValue::U32(value.as_u64()?.try_into().ok()?),
+        "U64" => SadValue::U64(value.as_u64()?),
+        "U128" => SadValue::U128(text?.parse().ok()?),
+        "I8" => SadValue::I8(value.as_i64()?.try_into().ok()?),
+        "I16" => SadValue::I16(value.as_i64()?.try_into().ok()?),
+        "I32" => SadValue::I32(value.as_i64()?.try_into().ok()?),
+        "I64" => SadValue::I64(value.as_i64()?),
+        "I128" => SadValue::I128(text?.parse().ok()?),
+        "F32" => SadValue::F32(value.as_f64()? as f32),
+        "F64" => SadValue::F64(value.as_f64()?),
+        "PublicKey" => SadValue::PublicKey(Pubkey::from_str(text?).ok()?),
+        _ => return None,
+    })
+}
+
+/// Splits the next `len` bytes from the buffer
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> SadTreeResult<&'a [u8]> {
+    if buf.len() < len {
+        return Err(SadTreeError::InsufficientData(len, buf.len()));
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head)
+}
+
+/// Borsh deserializes a type from exactly `len` bytes of the buffer
+fn take_borsh<T: BorshDeserialize>(
+    buf: &mut &[u8],
+    len: usize,
+    type_name: &str,
+) -> SadTreeResult<T> {
+    T::try_from_slice(take(buf, len)?)
+        .map_err(|e| SadTreeError::InvalidData(type_name.to_string(), e.to_string()))
+}
+
+pub trait SadElement {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue>;
+}
+
+impl SadElement for String {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        let mlen = take_borsh::<u32>(buf, 4, "String")? as usize;
+        string_from(take(buf, mlen)?)
+    }
+}
+
+/// String of UTF-8 bytes
+fn string_from(bytes: &[u8]) -> SadTreeResult<SadValue> {
+    match std::str::from_utf8(bytes) {
+        Ok(st) => Ok(SadValue::String(st.to_string())),
+        Err(e) => Err(SadTreeError::InvalidData(
+            "String".to_string(),
+            e.to_string(),
+        )),
+    }
+}
+
+impl SadElement for bool {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::Bool(take_borsh(buf, 1, "Bool")?))
+    }
+}
+
+impl SadElement for u8 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::U8(take_borsh(buf, 1, "U8")?))
+    }
+}
+
+impl SadElement for u16 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::U16(take_borsh(buf, 2, "U16")?))
+    }
+}
+
+impl SadElement for u32 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::U32(take_borsh(buf, 4, "U32")?))
+    }
+}
+
+impl SadElement for u64 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::U64(take_borsh(buf, 8, "U64")?))
+    }
+}
+
+impl SadElement for u128 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::U128(take_borsh(buf, 16, "U128")?))
+    }
+}
+
+impl SadElement for i8 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::I8(take_borsh(buf, 1, "I8")?))
+    }
+}
+
+impl SadElement for i16 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::I16(take_borsh(buf, 2, "I16")?))
+    }
+}
+
+impl SadElement for i32 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::I32(take_borsh(buf, 4, "I32")?))
+    }
+}
+
+impl SadElement for i64 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::I64(take_borsh(buf, 8, "I64")?))
+    }
+}
+
+impl SadElement for i128 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::I128(take_borsh(buf, 16, "I128")?))
+    }
+}
+
+impl SadElement for f32 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::F32(take_borsh(buf, 4, "F32")?))
+    }
+}
+
+impl SadElement for f64 {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::F64(take_borsh(buf, 8, "F64")?))
+    }
+}
+
+impl SadElement for Pubkey {
+    fn deser(buf: &mut &[u8]) -> SadTreeResult<SadValue> {
+        Ok(SadValue::PublicKey(take_borsh(buf, 32, "PublicKey")?))
+    }
+}
+
+/// Signature of the scalar deserializers
+type SadDeser = fn(&mut &[u8]) -> SadTreeResult<SadValue>;
+
+lazy_static! {
+    static ref DESER: HashMap<String, SadDeser> = {
+        let mut deser_table = HashMap::<String, SadDeser>::new();
+        deser_table.insert("String".to_string(), <String as SadElement>::deser);
+        deser_table.insert("Bool".to_string(), <bool as SadElement>::deser);
+        deser_table.insert("U8".to_string(), <u8 as SadElement>::deser);
+        deser_table.insert("U16".to_string(), <u16 as SadElement>::deser);
+        deser_table.insert("U32".to_string(), <u32 as SadElement>::deser);
+        deser_table.insert("U64".to_string(), <u64 as SadElement>::deser);
+        deser_table.insert("U128".to_string(), <u128 as SadElement>::deser);
+        deser_table.insert("I8".to_string(), <i8 as SadElement>::deser);
+        deser_table.insert("I16".to_string(), <i16 as SadElement>::deser);
+        deser_table.insert("I32".to_string(), <i32 as SadElement>::deser);
+        deser_table.insert("I64".to_string(), <i64 as SadElement>::deser);
+        deser_table.insert("I128".to_string(), <i128 as SadElement>::deser);
+        deser_table.insert("F32".to_string(), <f32 as SadElement>::deser);
+        deser_table.insert("F64".to_string(), <f64 as SadElement>::deser);
+        deser_table.insert("PublicKey".to_string(), <Pubkey as SadElement>::deser);
+        deser_table
+    };
+}
+
+pub fn deser_value_for(key: &String, stream: &mut &[u8]) -> SadTreeResult<SadValue> {
+    let dfn = DESER.get(key).unwrap();
+    dfn(stream)
+}
+
+/// Size in bytes of a scalar type, None for the length prefixed String
+pub fn fixed_size_of(key: &str) -> Option<usize> {
+    match key {
+        "Bool" | "U8" | "I8" => Some(1),
+        "U16" | "I16" => Some(2),
+        "U32" | "I32" | "F32" => Some(4),
+        "U64" | "I64" | "F64" => Some(8),
+        "U128" | "I128" => Some(16),
+        "PublicKey" => Some(32),
+        _ => None,
+    }
+}
+
+/// Serialization format of account data, which determines the size of
+/// length prefixes and the default Enum tag
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SadEncoding {
+    #[default]
+    Borsh,
+    Bincode,
+}
+
+impl SadEncoding {
+    /// Names of the encodings, as given to 'encoding:'
+    pub const NAMES: &'static [&'static str] = &["borsh", "bincode"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "borsh" => Some(SadEncoding::Borsh),
+            "bincode" => Some(SadEncoding::Bincode),
+            _ => None,
+        }
+    }
+
+    /// Enum discriminator type when no 'tag:' is declared
+    pub fn default_tag(&self) -> &'static str {
+        match self {
+            SadEncoding::Borsh => "U8",
+            SadEncoding::Bincode => "U32",
+        }
+    }
+
+    /// Reads a Vec, HashMap or String length prefix
+    pub fn deser_length(&self, stream: &mut &[u8]) -> SadTreeResult<usize> {
+        match self {
+            SadEncoding::Borsh => Ok(take_borsh::<u32>(stream, 4, "length")? as usize),
+            SadEncoding::Bincode => Ok(take_borsh::<u64>(stream, 8, "length")? as usize),
+        }
+    }
+
+    /// Deserializes a scalar value, with the encoding's String length prefix
+    pub fn deser_value_for(&self, key: &String, stream: &mut &[u8]) -> SadTreeResult<SadValue> {
+        match (self, key.as_str()) {
+            (SadEncoding::Bincode, "String") => {
+                let len = self.deser_length(stream)?;
+                string_from(take(stream, len)?)
+            }
+            _ => deser_value_for(key, stream),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_types() {
+        let vbool = vec![1u8];
+        let result = DESER.get("Bool").unwrap();
+        println!("{:?} {:?}", vbool, result(&mut vbool.as_slice()));
+    }
+
+    #[test]
+    fn test_serde_pass() {
+        let key = Pubkey::new_unique();
+        let value = SadValue::Tuple(vec![
+            SadValue::U128(u128::MAX),
+            SadValue::PublicKey(key),
+            SadValue::Option(vec![]),
+        ]);
+        let json_out = serde_json::to_value(&value).unwrap();
+        assert_eq!(
+            json_out,
+            serde_json::json!({"Tuple": [
+                {"U128": u128::MAX.to_string()},
+                {"PublicKey": key.to_string()},
+                {"Option": []}
+            ]})
+        );
+        assert_eq!(serde_json::from_value::<SadValue>(json_out).unwrap(), value);
+        assert_eq!(from_json_value_for("U8", &serde_json::json!(256)), None);
+        assert_eq!(
+            from_json_value_for("I128", &serde_json::json!("-5")),
+            Some(SadValue::I128(-5))
+        );
+    }
+
+    #[test]
+    fn test_bincode_string_pass() {
+        let mut data = 2u64.to_le_bytes().to_vec();
+        data.extend(b"hi!");
+        let stream = &mut data.as_slice();
+        match SadEncoding::Bincode.deser_value_for(&"String".to_string(), stream) {
+            Ok(SadValue::String(s)) => assert_eq!(s, "hi"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+        assert_eq!(*stream, b"!");
+    }
+
+    #[test]
+    fn test_short_data_fail() {
+        let data = [1u8, 0, 0];
+        assert!(matches!(
+            <u32 as SadElement>::deser(&mut &data[..]),
+            Err(SadTreeError::InsufficientData(4, 3))
+        ));
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend([0xffu8, 0xfe, 0xfd]);
+        assert!(matches!(
+            <String as SadElement>::deser(&mut data.as_slice()),
+            Err(SadTreeError::InvalidData(..))
+        ));
+        assert!(matches!(
+            <bool as SadElement>::deser(&mut &[2u8][..]),
+            Err(SadTreeError::InvalidData(..))
+        ));
+        assert!(SadEncoding::Bincode
+            .deser_value_for(
+                &"String".to_string(),
+                &mut &[9u8, 0, 0, 0, 0, 0, 0, 0, b'a'][..]
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,1383 @@
+//! @brief Solana queries
+
+use {
+    crate::{
+        desertree::Deseriaizer,
+        errors::{SadAccountErrorType, SadAccountResult, SadTreeResult},
+        loaderq::is_loader_account,
+        registry::DeclarationSelector,
+        sadtypes::SadValue,
+    },
+    serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer},
+    serde_json::{json, Value},
+    serde_with::{serde_as, DisplayFromStr},
+    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_client::{
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcContextConfig, RpcProgramAccountsConfig},
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcKeyedAccount},
+    },
+    solana_sdk::{
+        account::{Account, ReadableAccount},
+        clock::Slot,
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::Signature,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        rc::Rc,
+        str::FromStr,
+    },
+};
+
+/// Most accounts a single getMultipleAccounts request may ask for
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Identifies type of processing for deserialization
+#[serde_as]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum ResultForKeyType {
+    SingleAccount,
+    ProgramAccount(#[serde_as(as = "DisplayFromStr")] Pubkey),
+    MultipleAccounts,
+    Events,
+}
+
+/// Context of deserialization
+///
+/// Serializes the account with its data base64 encoded, the declaration
+/// and the deserialized values by item name. The values are deserialized
+/// again from the data when reloaded
+#[derive(Debug)]
+pub struct AccountResultContext {
+    key: Pubkey,
+    account: Account,
+    deserialized: Vec<SadValue>,
+    declaration: Rc<Deseriaizer>,
+}
+
+impl AccountResultContext {
+    pub fn new(
+        pkey: Pubkey,
+        acc: Account,
+        deser: Vec<SadValue>,
+        declaration: Rc<Deseriaizer>,
+    ) -> Self {
+        Self {
+            key: pkey,
+            account: acc,
+            deserialized: deser,
+            declaration,
+        }
+    }
+
+    pub fn pubkey(&self) -> &Pubkey {
+        &self.key
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// True when only a leading slice of the account data was fetched
+    pub fn is_sliced(&self) -> bool {
+        self.declaration.data_len().is_some()
+    }
+
+    pub fn deserialize_list(&self) -> &Vec<SadValue> {
+        &self.deserialized
+    }
+
+    /// Declaration the account data was deserialized with
+    pub fn declaration(&self) -> &Rc<Deseriaizer> {
+        &self.declaration
+    }
+
+    /// JSON representation of the deserialized data
+    pub fn to_json(&self) -> SadTreeResult<Value> {
+        self.declaration.to_json(&self.deserialized)
+    }
+
+    /// References to other accounts held in the deserialized data's
+    /// PublicKey fields, labeled with the path of the field holding them
+    pub fn references(&self) -> Vec<AccountReference> {
+        self.declaration
+            .item_values(&self.deserialized)
+            .public_keys()
+            .into_iter()
+            .filter(|(_, to)| *to != Pubkey::default())
+            .map(|(field, to)| AccountReference {
+                from: self.key,
+                field,
+                to,
+            })
+            .collect()
+    }
+}
+
+impl AccountResultContext {
+    /// Serializes with the declaration as given, the declaration itself
+    /// or its index in the declarations of a result
+    fn serialize_with<S: Serializer, D: Serialize + ?Sized>(
+        &self,
+        declaration: &D,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut record = serializer.serialize_struct("AccountResultContext", 8)?;
+        record.serialize_field("pubkey", &self.key.to_string())?;
+        record.serialize_field("owner", &self.account.owner.to_string())?;
+        record.serialize_field("lamports", &self.account.lamports)?;
+        record.serialize_field("executable", &self.account.executable)?;
+        record.serialize_field("rent_epoch", &self.account.rent_epoch)?;
+        record.serialize_field("data", &base64::encode(&self.account.data))?;
+        record.serialize_field("declaration", declaration)?;
+        record.serialize_field("values", &self.declaration.item_values(&self.deserialized))?;
+        record.end()
+    }
+}
+
+impl Serialize for AccountResultContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialize_with(&*self.declaration, serializer)
+    }
+}
+
+/// Serialized form of an AccountResultContext, with its declaration
+/// or the index of its declaration in a result
+#[serde_as]
+#[derive(Deserialize)]
+struct AccountRecord<D> {
+    #[serde_as(as = "DisplayFromStr")]
+    pubkey: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    owner: Pubkey,
+    lamports: u64,
+    executable: bool,
+    rent_epoch: u64,
+    data: String,
+    declaration: D,
+}
+
+impl<D> AccountRecord<D> {
+    /// Context of the record, its data deserialized again with the
+    /// declaration the record's declaration resolves to
+    fn into_context<E: de::Error>(
+        self,
+        resolve: impl FnOnce(D) -> Result<Rc<Deseriaizer>, E>,
+    ) -> Result<AccountResultContext, E> {
+        let declaration = resolve(self.declaration)?;
+        let data = base64::decode(&self.data).map_err(de::Error::custom)?;
+        let deserialized = declaration
+            .deser(&mut data.as_slice())
+            .map_err(de::Error::custom)?;
+        Ok(AccountResultContext::new(
+            self.pubkey,
+            Account {
+                lamports: self.lamports,
+                data,
+                owner: self.owner,
+                executable: self.executable,
+                rent_epoch: self.rent_epoch,
+            },
+            deserialized,
+            declaration,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountResultContext {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        AccountRecord::<Deseriaizer>::deserialize(deserializer)?
+            .into_context(|declaration| Ok(Rc::new(declaration)))
+    }
+}
+
+/// Reference from a deserialized account field to another account
+#[serde_as]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountReference {
+    #[serde_as(as = "DisplayFromStr")]
+    from: Pubkey,
+    field: String,
+    #[serde_as(as = "DisplayFromStr")]
+    to: Pubkey,
+}
+
+impl AccountReference {
+    pub fn from(&self) -> &Pubkey {
+        &self.from
+    }
+
+    pub fn field(&self) -> &String {
+        &self.field
+    }
+
+    pub fn to(&self) -> &Pubkey {
+        &self.to
+    }
+}
+
+/// Context of an event deserialized from program log data
+///
+/// Serializes with the declaration and the deserialized values by item
+/// name, from which the values are reloaded
+#[derive(Debug)]
+pub struct EventResultContext {
+    signature: Option<Signature>,
+    context_slot: Option<Slot>,
+    program_id: Pubkey,
+    deserialized: Vec<SadValue>,
+    declaration: Rc<Deseriaizer>,
+}
+
+impl EventResultContext {
+    pub fn new(
+        signature: Option<Signature>,
+        context_slot: Option<Slot>,
+        program_id: Pubkey,
+        deser: Vec<SadValue>,
+        declaration: Rc<Deseriaizer>,
+    ) -> Self {
+        Self {
+            signature,
+            context_slot,
+            program_id,
+            deserialized: deser,
+            declaration,
+        }
+    }
+
+    /// Signature of the transaction that emitted the event, if known
+    pub fn signature(&self) -> Option<&Signature> {
+        self.signature.as_ref()
+    }
+
+    /// Slot of the transaction or log notification the event was read
+    /// from, None when read from a file
+    pub fn context_slot(&self) -> Option<Slot> {
+        self.context_slot
+    }
+
+    /// Program that emitted the event
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    pub fn deserialize_list(&self) -> &Vec<SadValue> {
+        &self.deserialized
+    }
+
+    /// Declaration the event data was deserialized with
+    pub fn declaration(&self) -> &Rc<Deseriaizer> {
+        &self.declaration
+    }
+
+    /// Event name, the name of the declaration it was deserialized with
+    pub fn event_name(&self) -> &String {
+        self.declaration.tree().get_name()
+    }
+
+    /// JSON representation of the deserialized data
+    pub fn to_json(&self) -> SadTreeResult<Value> {
+        self.declaration.to_json(&self.deserialized)
+    }
+}
+
+impl EventResultContext {
+    /// Serializes with the declaration as given, the declaration itself
+    /// or its index in the declarations of a result
+    fn serialize_with<S: Serializer, D: Serialize + ?Sized>(
+        &self,
+        declaration: &D,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut record = serializer.serialize_struct("EventResultContext", 5)?;
+        record.serialize_field("signature", &self.signature.map(|s| s.to_string()))?;
+        record.serialize_field("context_slot", &self.context_slot)?;
+        record.serialize_field("program_id", &self.program_id.to_string())?;
+        record.serialize_field("declaration", declaration)?;
+        record.serialize_field("values", &self.declaration.item_values(&self.deserialized))?;
+        record.end()
+    }
+}
+
+impl Serialize for EventResultContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialize_with(&*self.declaration, serializer)
+    }
+}
+
+/// Serialized form of an EventResultContext, with its declaration
+/// or the index of its declaration in a result
+#[serde_as]
+#[derive(Deserialize)]
+struct EventRecord<D> {
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    signature: Option<Signature>,
+    #[serde(default)]
+    context_slot: Option<Slot>,
+    #[serde_as(as = "DisplayFromStr")]
+    program_id: Pubkey,
+    declaration: D,
+    values: Value,
+}
+
+impl<D> EventRecord<D> {
+    /// Context of the record, its values reloaded with the declaration
+    /// the record's declaration resolves to
+    fn into_context<E: de::Error>(
+        self,
+        resolve: impl FnOnce(D) -> Result<Rc<Deseriaizer>, E>,
+    ) -> Result<EventResultContext, E> {
+        let declaration = resolve(self.declaration)?;
+        let deserialized = declaration
+            .from_json(&self.values)
+            .map_err(de::Error::custom)?;
+        Ok(EventResultContext::new(
+            self.signature,
+            self.context_slot,
+            self.program_id,
+            deserialized,
+            declaration,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for EventResultContext {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        EventRecord::<Deseriaizer>::deserialize(deserializer)?
+            .into_context(|declaration| Ok(Rc::new(declaration)))
+    }
+}
+
+/// A context serializing with the index of its declaration in the
+/// declarations of the result holding it
+struct IndexedContext<'a, C> {
+    context: &'a C,
+    declaration: usize,
+}
+
+impl<'a, C> IndexedContext<'a, C> {
+    /// Contexts with the index of each one's declaration in `declarations`
+    fn all(
+        contexts: &'a [C],
+        declarations: &[&Rc<Deseriaizer>],
+        declaration: impl Fn(&C) -> &Rc<Deseriaizer>,
+    ) -> Vec<Self> {
+        contexts
+            .iter()
+            .map(|context| IndexedContext {
+                context,
+                declaration: declarations
+                    .iter()
+                    .position(|d| Rc::ptr_eq(d, declaration(context)))
+                    .unwrap(),
+            })
+            .collect()
+    }
+}
+
+impl Serialize for IndexedContext<'_, AccountResultContext> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.context.serialize_with(&self.declaration, serializer)
+    }
+}
+
+impl Serialize for IndexedContext<'_, EventResultContext> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.context.serialize_with(&self.declaration, serializer)
+    }
+}
+
+/// Receives each account as it is deserialized, with the slot it was
+/// read at, in place of the result holding it
+pub type AccountSink<'a> = Option<&'a mut dyn FnMut(&AccountResultContext, Slot)>;
+
+/// Generalized deserialization result
+///
+/// Serializes each distinct declaration once, in `declarations`, with
+/// the accounts and events referring to theirs by index
+#[derive(Debug)]
+pub struct DeserializationResult {
+    account_type: ResultForKeyType,
+    contexts: Vec<AccountResultContext>,
+    linked: Vec<AccountResultContext>,
+    unresolved: HashMap<Pubkey, Option<Pubkey>>,
+    failed: HashMap<Pubkey, String>,
+    missing: Vec<Pubkey>,
+    references: Vec<AccountReference>,
+    events: Vec<EventResultContext>,
+    context_slot: Option<Slot>,
+}
+
+/// Serialized form of a DeserializationResult
+#[serde_as]
+#[derive(Deserialize)]
+struct ResultRecord {
+    account_type: ResultForKeyType,
+    declarations: Vec<Deseriaizer>,
+    contexts: Vec<AccountRecord<usize>>,
+    linked: Vec<AccountRecord<usize>>,
+    #[serde_as(as = "HashMap<DisplayFromStr, Option<DisplayFromStr>>")]
+    unresolved: HashMap<Pubkey, Option<Pubkey>>,
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    #[serde(default)]
+    failed: HashMap<Pubkey, String>,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    missing: Vec<Pubkey>,
+    references: Vec<AccountReference>,
+    events: Vec<EventRecord<usize>>,
+    context_slot: Option<Slot>,
+}
+
+impl Serialize for DeserializationResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let declarations = self.declarations();
+        let mut record = serializer.serialize_struct("DeserializationResult", 10)?;
+        record.serialize_field("account_type", &self.account_type)?;
+        record.serialize_field(
+            "declarations",
+            &declarations
+                .iter()
+                .map(|d| d.as_ref())
+                .collect::<Vec<&Deseriaizer>>(),
+        )?;
+        record.serialize_field(
+            "contexts",
+            &IndexedContext::all(&self.contexts, &declarations, |c| &c.declaration),
+        )?;
+        record.serialize_field(
+            "linked",
+            &IndexedContext::all(&self.linked, &declarations, |c| &c.declaration),
+        )?;
+        record.serialize_field(
+            "unresolved",
+            &self
+                .unresolved
+                .iter()
+                .map(|(k, owner)| (k.to_string(), owner.map(|o| o.to_string())))
+                .collect::<HashMap<String, Option<String>>>(),
+        )?;
+        record.serialize_field(
+            "failed",
+            &self
+                .failed
+                .iter()
+                .map(|(k, e)| (k.to_string(), e))
+                .collect::<HashMap<String, &String>>(),
+        )?;
+        record.serialize_field(
+            "missing",
+            &self
+                .missing
+                .iter()
+                .map(|k| k.to_string())
+                .collect::<Vec<String>>(),
+        )?;
+        record.serialize_field("references", &self.references)?;
+        record.serialize_field(
+            "events",
+            &IndexedContext::all(&self.events, &declarations, |e| &e.declaration),
+        )?;
+        record.serialize_field("context_slot", &self.context_slot)?;
+        record.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DeserializationResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = ResultRecord::deserialize(deserializer)?;
+        let declarations = record
+            .declarations
+            .into_iter()
+            .map(Rc::new)
+            .collect::<Vec<Rc<Deseriaizer>>>();
+        let resolve = |index: usize| {
+            declarations
+                .get(index)
+                .cloned()
+                .ok_or_else(|| de::Error::custom(format!("No declaration at index {}", index)))
+        };
+        let accounts = |records: Vec<AccountRecord<usize>>| {
+            records
+                .into_iter()
+                .map(|r| r.into_context(resolve))
+                .collect::<Result<Vec<AccountResultContext>, D::Error>>()
+        };
+        Ok(DeserializationResult {
+            account_type: record.account_type,
+            contexts: accounts(record.contexts)?,
+            linked: accounts(record.linked)?,
+            unresolved: record.unresolved,
+            failed: record.failed,
+            missing: record.missing,
+            references: record.references,
+            events: record
+                .events
+                .into_iter()
+                .map(|r| r.into_context(resolve))
+                .collect::<Result<Vec<EventResultContext>, D::Error>>()?,
+            context_slot: record.context_slot,
+        })
+    }
+}
+
+impl DeserializationResult {
+    pub fn new(account_type: ResultForKeyType, contexts: Vec<AccountResultContext>) -> Self {
+        Self {
+            account_type,
+            contexts,
+            linked: Vec::<AccountResultContext>::new(),
+            unresolved: HashMap::<Pubkey, Option<Pubkey>>::new(),
+            failed: HashMap::<Pubkey, String>::new(),
+            missing: Vec::<Pubkey>::new(),
+            references: Vec::<AccountReference>::new(),
+            events: Vec::<EventResultContext>::new(),
+            context_slot: None,
+        }
+    }
+
+    /// Distinct declarations of the accounts and events, in the order
+    /// they are first used
+    fn declarations(&self) -> Vec<&Rc<Deseriaizer>> {
+        let mut declarations = Vec::<&Rc<Deseriaizer>>::new();
+        let used = self
+            .contexts
+            .iter()
+            .chain(&self.linked)
+            .map(|c| &c.declaration)
+            .chain(self.events.iter().map(|e| &e.declaration));
+        for declaration in used {
+            if !declarations.iter().any(|d| Rc::ptr_eq(d, declaration)) {
+                declarations.push(declaration)
+            }
+        }
+        declarations
+    }
+
+    /// Result of events deserialized from program logs
+    pub fn from_events(events: Vec<EventResultContext>) -> Self {
+        Self {
+            events,
+            ..DeserializationResult::new(
+                ResultForKeyType::Events,
+                Vec::<AccountResultContext>::new(),
+            )
+        }
+    }
+
+    pub fn account_type(&self) -> &ResultForKeyType {
+        &self.account_type
+    }
+
+    pub fn context_count(&self) -> usize {
+        self.contexts.len()
+    }
+
+    pub fn context_vec(&self) -> &Vec<AccountResultContext> {
+        &self.contexts
+    }
+
+    pub fn event_vec(&self) -> &Vec<EventResultContext> {
+        &self.events
+    }
+
+    /// Accounts reached by following references
+    pub fn linked_vec(&self) -> &Vec<AccountResultContext> {
+        &self.linked
+    }
+
+    /// Referenced accounts that do not exist (None) or have no
+    /// matching declaration for their owner (Some(owner))
+    pub fn unresolved(&self) -> &HashMap<Pubkey, Option<Pubkey>> {
+        &self.unresolved
+    }
+
+    /// Accounts with a matching declaration whose data failed
+    /// deserializing, with the error
+    pub fn failed(&self) -> &HashMap<Pubkey, String> {
+        &self.failed
+    }
+
+    /// Requested accounts that are not deserialized, being unresolved
+    pub fn missing_vec(&self) -> &Vec<Pubkey> {
+        &self.missing
+    }
+
+    /// References followed between accounts
+    pub fn references(&self) -> &Vec<AccountReference> {
+        &self.references
+    }
+
+    /// Slot of the RPC responses the accounts were read at, None
+    /// when no accounts were read
+    pub fn context_slot(&self) -> Option<Slot> {
+        self.context_slot
+    }
+
+    /// Deserializes an account with the declaration selected for it.
+    /// An account that is not deserialized is held as unresolved, with
+    /// the error when its data does not fit the declaration
+    fn decode(
+        &mut self,
+        key: &Pubkey,
+        account: &Account,
+        selector: &dyn DeclarationSelector,
+    ) -> Option<(Rc<Deseriaizer>, Vec<SadValue>)> {
+        let decoded = selector
+            .declaration_for(&account.owner, account.data())
+            .map(|d| d.deser(&mut account.data()).map(|v| (d, v)));
+        match decoded {
+            Some(Ok(decoded)) => return Some(decoded),
+            Some(Err(e)) => {
+                self.failed.insert(*key, e.to_string());
+            }
+            None => {}
+        }
+        self.unresolved.insert(*key, Some(account.owner));
+        None
+    }
+
+    /// Holds a deserialized account, or sends it to the sink
+    fn push_context(&mut self, context: AccountResultContext, sink: &mut AccountSink) {
+        match sink {
+            Some(sink) => sink(&context, self.context_slot.unwrap_or_default()),
+            None => self.contexts.push(context),
+        }
+    }
+
+    /// True if references were followed
+    pub fn is_followed(&self) -> bool {
+        !self.references.is_empty()
+    }
+
+    /// Deserialized account, requested or linked, for a key
+    pub fn context_for(&self, key: &Pubkey) -> Option<&AccountResultContext> {
+        self.contexts
+            .iter()
+            .chain(self.linked.iter())
+            .find(|c| c.pubkey() == key)
+    }
+}
+/// Retrieves a single account from RPC cluster
+///
+/// Presumes that the key is a program owned account
+pub fn solana_account(rpc_client: &RpcClient, key: &Pubkey) -> SadAccountResult<Account> {
+    match rpc_client.get_account(key) {
+        Ok(acc) => Ok(acc),
+        Err(e) => {
+            eprintln!("{}", e);
+            Err(SadAccountErrorType::FailedAccountGet)
+        }
+    }
+}
+
+/// Account request configuration, fetching only the first
+/// `data_len` bytes of account data when given, from a node
+/// that has reached `min_context_slot`
+fn account_config(
+    rpc_client: &RpcClient,
+    data_len: Option<usize>,
+    min_context_slot: Option<Slot>,
+) -> RpcAccountInfoConfig {
+    RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        data_slice: data_len.map(|length| UiDataSliceConfig { offset: 0, length }),
+        commitment: Some(rpc_client.commitment()),
+        min_context_slot,
+    }
+}
+
+/// Commitment of transaction requests, the client's commitment
+/// with `processed` raised to `confirmed`, which getTransaction
+/// does not accept
+pub fn transaction_commitment(rpc_client: &RpcClient) -> CommitmentConfig {
+    let commitment = rpc_client.commitment();
+    if commitment.is_at_least_confirmed() {
+        commitment
+    } else {
+        CommitmentConfig::confirmed()
+    }
+}
+
+/// Fails unless the RPC node has reached `min_context_slot`, for
+/// requests such as getTransaction that do not take one
+pub fn check_context_slot(
+    rpc_client: &RpcClient,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<()> {
+    if let Some(slot) = min_context_slot {
+        let config = RpcContextConfig {
+            commitment: Some(rpc_client.commitment()),
+            min_context_slot: Some(slot),
+        };
+        if let Err(e) = rpc_client.send::<Slot>(RpcRequest::GetSlot, json!([config])) {
+            eprintln!("{}", e);
+            return Err(SadAccountErrorType::ContextSlotNotReachedError(slot));
+        }
+    }
+    Ok(())
+}
+
+/// Retrieves a single account from RPC cluster with at most
+/// `data_len` bytes of its data, and the slot of the response
+pub fn solana_account_slice(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    data_len: Option<usize>,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<(Slot, Account)> {
+    let config = account_config(rpc_client, data_len, min_context_slot);
+    match rpc_client.get_account_with_config(key, config) {
+        Ok(Response {
+            context,
+            value: Some(account),
+        }) => Ok((context.slot, account)),
+        Ok(_) => {
+            eprintln!("AccountNotFound: pubkey={}", key);
+            Err(SadAccountErrorType::FailedAccountGet)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            Err(SadAccountErrorType::FailedAccountGet)
+        }
+    }
+}
+
+/// Retrieves a list of accounts from RPC cluster, with at most
+/// `data_len` bytes of their data, and the slot of the response
+///
+/// Presumes that the key is the Program key for which
+/// multiple Program Owned Accounts exist. A `data_len` of
+/// 0 scans for the account keys only
+pub fn solana_program_accounts(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    data_len: Option<usize>,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<(Slot, Vec<(Pubkey, Account)>)> {
+    let (slot, vaccount) = solana_account_slice(rpc_client, key, Some(0), min_context_slot)?;
+    if vaccount.executable() != true {
+        return Err(SadAccountErrorType::NotProgramKeyError);
+    }
+    let config = RpcProgramAccountsConfig {
+        account_config: account_config(rpc_client, data_len, Some(slot)),
+        with_context: Some(true),
+        ..RpcProgramAccountsConfig::default()
+    };
+    // The client drops the context of the response, so request it directly
+    let response = match rpc_client.send::<Response<Vec<RpcKeyedAccount>>>(
+        RpcRequest::GetProgramAccounts,
+        json!([key.to_string(), config]),
+    ) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(SadAccountErrorType::FailedProgramAccountGet);
+        }
+    };
+    let mut accounts = Vec::<(Pubkey, Account)>::with_capacity(response.value.len());
+    for keyed in response.value {
+        match (Pubkey::from_str(&keyed.pubkey), keyed.account.decode()) {
+            (Ok(k), Some(a)) => accounts.push((k, a)),
+            _ => return Err(SadAccountErrorType::FailedProgramAccountGet),
+        }
+    }
+    Ok((response.context.slot, accounts))
+}
+
+/// Retrieves accounts from RPC cluster in batches, with at most
+/// `data_len` bytes of their data, and the slot of the last batch
+///
+/// Result is in key order, None where no account exists. Each
+/// batch is from a slot no earlier than the batch before it. The
+/// slot is `min_context_slot` when there are no keys to fetch
+pub fn solana_multiple_accounts(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+    data_len: Option<usize>,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<(Option<Slot>, Vec<Option<Account>>)> {
+    let mut accounts = Vec::<Option<Account>>::with_capacity(keys.len());
+    let mut slot = min_context_slot;
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        match rpc_client
+            .get_multiple_accounts_with_config(chunk, account_config(rpc_client, data_len, slot))
+        {
+            Ok(mut batch) => {
+                slot = Some(batch.context.slot);
+                accounts.append(&mut batch.value)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return Err(SadAccountErrorType::FailedMultipleAccountGet);
+            }
+        }
+    }
+    Ok((slot, accounts))
+}
+
+/// Deserialize a single Account
+pub fn deserialize_account(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    selector: &dyn DeclarationSelector,
+    min_context_slot: Option<Slot>,
+) -> SadAccountResult<DeserializationResult> {
+    let (slot, solacc) =
+        solana_account_slice(rpc_client, key, selector.data_len(), min_context_slot)?;
+    // Upgradeable loader program accounts hold loader state
+    if solacc.executable() && !is_loader_account(&solacc) {
+        return Err(SadAccountErrorType::AccountIsExecutableError);
+    }
+    let destree = match selector.declaration_for(&solacc.owner, solacc.data()) {
+        Some(d) => d,
+        None => return Err(SadAccountErrorType::NoDeclarationError(solacc.owner)),
+    };
+    let mut resvec = Vec::<AccountResultContext>::new();
+    match destree.deser(&mut solacc.data()) {
+        Ok(res) => {
+            resvec.push(AccountResultContext::new(key.clone(), solacc, res, destree));
+            let mut result = DeserializationResult::new(ResultForKeyType::SingleAccount, resvec);
+            result.context_slot = Some(slot);
+            Ok(result)
+        }
+        Err(e) => Err(SadAccountErrorType::DeserializationError(*key, e)),
+    }
+}
+
+/// Deserializes an account of a program, skipping it when no
+/// declaration matches
+fn push_program_account(
+    result: &mut DeserializationResult,
+    program: &Pubkey,
+    (key, account): (Pubkey, Account),
+    selector: &dyn DeclarationSelector,
+    sink: &mut AccountSink,
+) -> SadAccountResult<()> {
+    let destree = match selector.declaration_for(program, account.data()) {
+        Some(d) => d,
+        None => {
+            eprintln!("No declaration matches account {}, skipping", key);
+            return Ok(());
+        }
+    };
+    match destree.deser(&mut account.data()) {
+        Ok(res) => {
+            result.push_context(AccountResultContext::new(key, account, res, destree), sink);
+            Ok(())
+        }
+        Err(e) => Err(SadAccountErrorType::DeserializationError(key, e)),
+    }
+}
+
+/// Deserialize all Program Owned Accounts, sending each to the sink
+/// as it is deserialized when one is given
+///
+/// When `paged` the program is scanned for account keys only and
+/// the accounts are then fetched in batches, keeping responses
+/// under RPC size limits
+pub fn deserialize_program_accounts(
+    rpc_client: &RpcClient,
+    key: &Pubkey,
+    selector: &dyn DeclarationSelector,
+    min_context_slot: Option<Slot>,
+    paged: bool,
+    mut sink: AccountSink,
+) -> SadAccountResult<DeserializationResult> {
+    let data_len = selector.data_len();
+    let mut result = DeserializationResult::new(ResultForKeyType::ProgramAccount(*key), Vec::new());
+    let scan_len = if paged { Some(0) } else { data_len };
+    let (slot, solacc) = solana_program_accounts(rpc_client, key, scan_len, min_context_slot)?;
+    result.context_slot = Some(slot);
+    if !paged {
+        for acc in solacc {
+            push_program_account(&mut result, key, acc, selector, &mut sink)?;
+        }
+        return Ok(result);
+    }
+    let keys = solacc.into_iter().map(|(k, _)| k).collect::<Vec<Pubkey>>();
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let (slot, accounts) =
+            solana_multiple_accounts(rpc_client, chunk, data_len, result.context_slot)?;
+        result.context_slot = slot;
+        // Accounts closed between the scan and the fetch are dropped
+        for acc in chunk.iter().zip(accounts) {
+            if let (k, Some(a)) = acc {
+                push_program_account(&mut result, key, (*k, a), selector, &mut sink)?;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Deserialize accounts of many keys, fetched in batches and sent to
+/// the sink as they are deserialized when one is given. Accounts
+/// that do not exist or have no matching declaration are reported
+/// as missing rather than failing
+pub fn deserialize_accounts(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+    selector: &dyn DeclarationSelector,
+    min_context_slot: Option<Slot>,
+    mut sink: AccountSink,
+) -> SadAccountResult<DeserializationResult> {
+    let mut result = DeserializationResult::new(ResultForKeyType::MultipleAccounts, Vec::new());
+    result.context_slot = min_context_slot;
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let (slot, accounts) =
+            solana_multiple_accounts(rpc_client, chunk, selector.data_len(), result.context_slot)?;
+        result.context_slot = slot;
+        for (key, account) in chunk.iter().zip(accounts) {
+            let account = match account {
+                Some(account) => account,
+                None => {
+                    result.unresolved.insert(*key, None);
+                    result.missing.push(*key);
+                    continue;
+                }
+            };
+            match result.decode(key, &account, selector) {
+                Some((destree, res)) => result.push_context(
+                    AccountResultContext::new(*key, account, res, destree),
+                    &mut sink,
+                ),
+                None => result.missing.push(*key),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Follows the public key references of deserialized accounts for
+/// `depth` levels, deserializing each referenced account with the
+/// declaration selected by its owner
+pub fn follow_references(
+    rpc_client: &RpcClient,
+    result: &mut DeserializationResult,
+    selector: &dyn DeclarationSelector,
+    depth: usize,
+) -> SadAccountResult<()> {
+    let mut visited = result
+        .contexts
+        .iter()
+        .map(|c| c.key)
+        .collect::<HashSet<Pubkey>>();
+    let mut level = result
+        .contexts
+        .iter()
+        .flat_map(|c| c.references())
+        .collect::<Vec<AccountReference>>();
+    for _ in 0..depth {
+        let fetch = level
+            .iter()
+            .filter_map(|r| visited.insert(r.to).then_some(r.to))
+            .collect::<Vec<Pubkey>>();
+        result.references.append(&mut level);
+        // Linked accounts are from no earlier a slot than those linking them
+        let (slot, accounts) =
+            solana_multiple_accounts(rpc_client, &fetch, None, result.context_slot)?;
+        result.context_slot = slot;
+        for (key, account) in fetch.into_iter().zip(accounts) {
+            let account = match account {
+                Some(account) => account,
+                None => {
+                    result.unresolved.insert(key, None);
+                    continue;
+                }
+            };
+            if let Some((destree, res)) = result.decode(&key, &account, selector) {
+                let context = AccountResultContext::new(key, account, res, destree);
+                level.extend(context.references());
+                result.linked.push(context)
+            }
+        }
+        if level.is_empty() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::SadTreeError,
+        library::{layout, SPL_TOKEN_PROGRAM_ID},
+        registry::SadRegistry,
+    };
+    use borsh::BorshSerialize;
+    use solana_account_decoder::UiAccount;
+    use std::str::FromStr;
+
+    use gadgets_common::load_yaml_file;
+    use solana_cli_config::*;
+    // Presume solana-cli-program accounts
+    const SCLI: &str = "../../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml";
+
+    fn get_config_rpcclient() -> SadAccountResult<(Config, RpcClient)> {
+        if let Some(x) = &*CONFIG_FILE {
+            let cfg = match Config::load(&x) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Err(SadAccountErrorType::ConfigFileError);
+                }
+            };
+            let rpc_client = RpcClient::new(cfg.json_rpc_url.clone());
+            Ok((cfg, rpc_client))
+        } else {
+            Err(SadAccountErrorType::ConfigFileError)
+        }
+    }
+
+    #[test]
+    fn test_account_references_pass() {
+        let mint = Pubkey::new_from_array([1u8; 32]);
+        let owner = Pubkey::new_from_array([2u8; 32]);
+        let delegate = Pubkey::new_from_array([3u8; 32]);
+        let mut data = vec![0u8; 165];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[72] = 1;
+        data[76..108].copy_from_slice(delegate.as_ref());
+        // The owner is also the close authority
+        data[129] = 1;
+        data[133..165].copy_from_slice(owner.as_ref());
+        let declaration = Rc::new(Deseriaizer::new(&layout("spl_token_account").unwrap()[0]));
+        let deser = declaration.deser(&mut data.as_slice()).unwrap();
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let key = Pubkey::new_unique();
+        let context = AccountResultContext::new(key, account, deser, declaration);
+        let mut refs = context
+            .references()
+            .into_iter()
+            .map(|r| (r.field().clone(), *r.to()))
+            .collect::<Vec<(String, Pubkey)>>();
+        refs.sort();
+        assert_eq!(
+            refs,
+            vec![
+                ("close_authority.value".to_string(), owner),
+                ("delegate.value".to_string(), delegate),
+                ("mint".to_string(), mint),
+                ("owner".to_string(), owner),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_account_references_string_fail() {
+        let declaration = Rc::new(
+            Deseriaizer::from_yaml_str(
+                "holder:\n  - owner:\n      type: PublicKey\n  - label:\n      type: String\n",
+            )
+            .unwrap(),
+        );
+        let owner = Pubkey::new_unique();
+        let data = (owner.to_bytes(), owner.to_string()).try_to_vec().unwrap();
+        let deser = declaration.deser(&mut data.as_slice()).unwrap();
+        let account = Account::new(1, data.len(), &Pubkey::default());
+        let context = AccountResultContext::new(Pubkey::new_unique(), account, deser, declaration);
+        // A String holding a key's text is not a reference
+        let refs = context.references();
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].field(), "owner");
+    }
+
+    #[test]
+    fn test_follow_references_pass() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let token_program = Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).unwrap();
+        let mut data = vec![0u8; 165];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        let declaration = Rc::new(Deseriaizer::new(&layout("spl_token_account").unwrap()[0]));
+        let deser = declaration.deser(&mut data.as_slice()).unwrap();
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let key = Pubkey::new_unique();
+        let mut result = DeserializationResult::new(
+            ResultForKeyType::SingleAccount,
+            vec![AccountResultContext::new(key, account, deser, declaration)],
+        );
+        // The mint exists without a mint authority, the owner does not
+        let mut mint_data = vec![0u8; 82];
+        mint_data[44] = 6;
+        mint_data[45] = 1;
+        let mint_account = Account {
+            lamports: 1,
+            data: mint_data,
+            owner: token_program,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            json!({
+                "context": {"slot": 7},
+                "value": [
+                    UiAccount::encode(&mint, &mint_account, UiAccountEncoding::Base64, None, None),
+                    null,
+                ],
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        follow_references(&rpc_client, &mut result, &SadRegistry::new(None), 2).unwrap();
+        let refs = result
+            .references()
+            .iter()
+            .map(|r| (r.field().clone(), *r.to()))
+            .collect::<Vec<(String, Pubkey)>>();
+        assert_eq!(
+            refs,
+            vec![("mint".to_string(), mint), ("owner".to_string(), owner)]
+        );
+        assert_eq!(result.linked_vec().len(), 1);
+        assert_eq!(
+            result.context_for(&mint).unwrap().to_json().unwrap()["decimals"],
+            json!(6)
+        );
+        assert_eq!(result.unresolved().get(&owner), Some(&None));
+        assert_eq!(result.context_slot(), Some(7));
+    }
+
+    #[test]
+    fn test_transaction_commitment_pass() {
+        let commitment = |c| {
+            transaction_commitment(&RpcClient::new_with_commitment(
+                "http://localhost:8899".to_string(),
+                c,
+            ))
+        };
+        assert_eq!(
+            commitment(CommitmentConfig::processed()),
+            CommitmentConfig::confirmed()
+        );
+        assert_eq!(
+            commitment(CommitmentConfig::finalized()),
+            CommitmentConfig::finalized()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_accounts_short_data_fail() {
+        let declaration =
+            Rc::new(Deseriaizer::from_yaml_str("counter:\n  - count:\n      type: U64\n").unwrap());
+        let (short, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let owner = Pubkey::new_unique();
+        let account = Account {
+            lamports: 1,
+            data: vec![1u8; 4],
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            json!({
+                "context": {"slot": 3},
+                "value": [
+                    UiAccount::encode(&short, &account, UiAccountEncoding::Base64, None, None),
+                    null,
+                ],
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let result =
+            deserialize_accounts(&rpc_client, &[short, other], &declaration, None, None).unwrap();
+        // Data that does not fit the declaration keeps its error
+        assert_eq!(result.missing_vec(), &vec![short, other]);
+        assert_eq!(result.unresolved().get(&short), Some(&Some(owner)));
+        assert_eq!(
+            result.failed().get(&short),
+            Some(&SadTreeError::InsufficientData(8, 4).to_string())
+        );
+        assert!(result.failed().get(&other).is_none());
+        let reloaded =
+            serde_json::from_value::<DeserializationResult>(serde_json::to_value(&result).unwrap())
+                .unwrap();
+        assert_eq!(reloaded.failed(), result.failed());
+    }
+
+    #[test]
+    fn test_result_serde_pass() {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; 165];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[64..72].copy_from_slice(&u64::MAX.to_le_bytes());
+        let declaration = Rc::new(Deseriaizer::new(&layout("spl_token_account").unwrap()[0]));
+        let deser = declaration.deser(&mut data.as_slice()).unwrap();
+        let account = Account {
+            lamports: 2039280,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 361,
+        };
+        let key = Pubkey::new_unique();
+        let program_id = account.owner;
+        let context =
+            AccountResultContext::new(key, account.clone(), deser.clone(), declaration.clone());
+        let other = AccountResultContext::new(
+            Pubkey::new_unique(),
+            account,
+            deser.clone(),
+            declaration.clone(),
+        );
+        let mut result = DeserializationResult::new(
+            ResultForKeyType::ProgramAccount(program_id),
+            vec![context, other],
+        );
+        result.missing.push(mint);
+        result.unresolved.insert(mint, None);
+        let json_out = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json_out["account_type"],
+            json!({"ProgramAccount": program_id.to_string()})
+        );
+        // Accounts sharing a declaration refer to it by index
+        assert_eq!(json_out["declarations"].as_array().unwrap().len(), 1);
+        assert_eq!(json_out["contexts"][1]["declaration"], json!(0));
+        let account_out = &json_out["contexts"][0];
+        assert_eq!(account_out["declaration"], json!(0));
+        assert_eq!(account_out["pubkey"], json!(key.to_string()));
+        assert_eq!(account_out["values"]["mint"], json!(mint.to_string()));
+        assert_eq!(account_out["values"]["amount"], json!(u64::MAX));
+        assert_eq!(json_out["unresolved"][mint.to_string()], Value::Null);
+
+        let reloaded = serde_json::from_value::<DeserializationResult>(json_out).unwrap();
+        assert_eq!(
+            reloaded.account_type(),
+            &ResultForKeyType::ProgramAccount(program_id)
+        );
+        let context = &reloaded.context_vec()[0];
+        assert_eq!(context.pubkey(), &key);
+        assert_eq!(context.account(), result.context_vec()[0].account());
+        assert_eq!(context.deserialize_list(), &deser);
+        assert_eq!(reloaded.missing_vec(), &vec![mint]);
+        assert_eq!(reloaded.unresolved().get(&mint), Some(&None));
+        assert!(Rc::ptr_eq(
+            context.declaration(),
+            reloaded.context_vec()[1].declaration()
+        ));
+
+        // A declaration index outside the declarations fails
+        let mut json_out = serde_json::to_value(&result).unwrap();
+        json_out["contexts"][1]["declaration"] = json!(1);
+        assert!(serde_json::from_value::<DeserializationResult>(json_out).is_err());
+
+        // Events reload from their values
+        let event = EventResultContext::new(None, Some(9), program_id, deser, declaration);
+        let reloaded =
+            serde_json::from_value::<EventResultContext>(serde_json::to_value(&event).unwrap())
+                .unwrap();
+        assert_eq!(reloaded.program_id(), &program_id);
+        assert_eq!(reloaded.context_slot(), Some(9));
+        assert_eq!(reloaded.to_json().unwrap(), event.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_fetch_pda_pass() {
+        let (_, rpc_client) = get_config_rpcclient().unwrap();
+        // Presume solana-cli-program accounts are created and run either locally or devnet
+        let pubkey = Pubkey::from_str("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv").unwrap();
+        let pda = Pubkey::create_program_address(&[b"test"], &pubkey).unwrap();
+        println!("PDA PubKey {:?}", pda);
+        println!("{:?}", rpc_client.get_account(&pda));
+    }
+
+    #[test]
+    fn test_fetch_singleaccount_pass() {
+        let (_, rpc_client) = get_config_rpcclient().unwrap();
+        // Presume solana-cli-program accounts are created and run either locally or devnet
+        let pubkey = Pubkey::from_str("5gMsBeLmPkwEKQ1H2AwceAPasXLyZ4tvWGCYR59qf47U").unwrap();
+        let x = solana_account(&rpc_client, &pubkey);
+        assert!(x.is_ok());
+        println!("{:?}", x.unwrap());
+    }
+    #[test]
+    fn test_fetch_programaccounts_pass() {
+        let (_, rpc_client) = get_config_rpcclient().unwrap();
+        // Presume solana-cli-program accounts are created and run either locally or devnet
+        let pubkey = Pubkey::from_str("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv").unwrap();
+        // let pubkey = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let x = solana_program_accounts(&rpc_client, &pubkey, None, None);
+        assert!(x.is_ok());
+        println!("{:?}", x.unwrap());
+    }
+
+    #[test]
+    fn test_deserialize_singleaccount_pass() {
+        let (_, rpc_client) = get_config_rpcclient().unwrap();
+        // Presume solana-cli-program accounts are created and run either locally or devnet
+        let pubkey = Pubkey::from_str("5gMsBeLmPkwEKQ1H2AwceAPasXLyZ4tvWGCYR59qf47U").unwrap();
+        let yamldecl = load_yaml_file(SCLI).unwrap();
+        let deser = deserialize_account(
+            &rpc_client,
+            &pubkey,
+            &Rc::new(Deseriaizer::new(&yamldecl[0])),
+            None,
+        )
+        .unwrap();
+        assert_eq!(deser.context_count(), 1);
+        assert_eq!(deser.account_type(), &ResultForKeyType::SingleAccount);
+        let oneresult = deser.context_vec().first().unwrap();
+        assert_eq!(oneresult.pubkey(), &pubkey);
+        println!("{:?}", oneresult.deserialize_list());
+    }
+    #[test]
+    fn test_deserialize_programaccount_pass() {
+        let (_, rpc_client) = get_config_rpcclient().unwrap();
+        // Presume solana-cli-program accounts are created and run either locally or devnet
+        let pubkey = Pubkey::from_str("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv").unwrap();
+        let onekey = Pubkey::from_str("A94wMjV54C8f8wn7zL8TxNCdNiGoq7XSN7vWGrtd4vwU").unwrap();
+        let twokey = Pubkey::from_str("5gMsBeLmPkwEKQ1H2AwceAPasXLyZ4tvWGCYR59qf47U").unwrap();
+        let yamldecl = load_yaml_file(SCLI).unwrap();
+        let data_declaration = Rc::new(Deseriaizer::new(&yamldecl[0]));
+        let deser = deserialize_program_accounts(
+            &rpc_client,
+            &pubkey,
+            &data_declaration,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(deser.context_count(), 2);
+        assert_eq!(
+            deser.account_type(),
+            &ResultForKeyType::ProgramAccount(pubkey)
+        );
+
+        let key_list = [&onekey, &twokey];
+        let cvec = deser.context_vec();
+        for i in 0..2 {
+            assert_eq!(
+                key_list.iter().find(|x| *x == &cvec[i].pubkey()),
+                Some(&cvec[i].pubkey())
+            );
+        }
+        for c in deser.context_vec() {
+            println!("HL Elements {}", c.deserialize_list().len());
+            println!("Data {:?}", c.deserialize_list());
+        }
+    }
+}
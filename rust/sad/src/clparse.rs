@@ -5,11 +5,24 @@ use {
         crate_description, crate_name, crate_version, App, AppSettings, Arg, ArgGroup, ArgMatches,
     },
     gadgets_common::load_yaml_file,
+    gadgets_sad::{
+        library::{layout, layout_names, sysvar_names},
+        SadRegistry,
+    },
     lazy_static::*,
-    solana_clap_utils::input_validators::{is_keypair, is_pubkey, is_url_or_moniker},
-    solana_sdk::{pubkey::Pubkey, signature::read_keypair_file, signer::Signer},
+    solana_clap_utils::input_validators::{is_keypair, is_parsable, is_pubkey, is_url_or_moniker},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Signature},
+        signer::Signer,
+    },
     std::process::exit,
-    std::{collections::HashMap, str::FromStr},
+    std::{
+        collections::HashMap,
+        fs::read_to_string,
+        io::{self, Read},
+        str::FromStr,
+    },
     yaml_rust::Yaml,
 };
 
@@ -52,6 +65,24 @@ pub fn parse_command_line() -> App<'static, 'static> {
                 .validator(is_url_or_moniker)
                 .help("JSON RPC URL for the cluster [default: value from configuration file]"),
         )
+        .arg(
+            Arg::with_name("commitment")
+                .long("commitment")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["processed", "confirmed", "finalized"])
+                .default_value("confirmed")
+                .help("Commitment level of account, transaction and log queries"),
+        )
+        .arg(
+            Arg::with_name("min_context_slot")
+                .long("min-context-slot")
+                .value_name("SLOT")
+                .global(true)
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Minimum slot the RPC node must have reached to answer account and transaction queries"),
+        )
         .arg(
             Arg::with_name("decl")
                 .display_order(2)
@@ -61,6 +92,28 @@ pub fn parse_command_line() -> App<'static, 'static> {
                 .global(true)
                 .help("YAML data deserialization declaration file"),
         )
+        .arg(
+            Arg::with_name("layout")
+                .display_order(3)
+                .long("layout")
+                .short("l")
+                .takes_value(true)
+                .global(true)
+                .conflicts_with("decl")
+                .possible_values(layout_names())
+                .help("Bundled data deserialization declaration name"),
+        )
+        .arg(
+            Arg::with_name("registry")
+                .display_order(4)
+                .long("registry")
+                .short("r")
+                .value_name("DIR")
+                .takes_value(true)
+                .global(true)
+                .env("SAD_REGISTRY")
+                .help("Directory of '<program id>.yml' declarations used when no declaration is given"),
+        )
         .arg(
             Arg::with_name("keypair")
                 .long("keypair")
@@ -96,10 +149,17 @@ pub fn parse_command_line() -> App<'static, 'static> {
                 .short("o")
                 .global(true)
                 .takes_value(true)
-                .possible_values(&["json", "stdout"])
-                .default_value("stdout")
-                .requires_ifs(&[("json", "filename")])
-                .help("Direct output to file"),
+                .possible_values(&["tree", "json", "ndjson", "stdout", "yaml", "toml", "sqlite", "parquet"])
+                .default_value("tree")
+                .requires_ifs(&[("json", "filename"), ("sqlite", "filename"), ("parquet", "filename")])
+                .help("Print a tree or pretty JSON ('stdout'), direct output to file, newline delimited JSON, YAML or TOML to file or stdout, or accounts to an SQLite database or Parquet file"),
+        )
+        .arg(
+            Arg::with_name("no_color")
+                .long("no-color")
+                .global(true)
+                .takes_value(false)
+                .help("Print the tree output without color"),
         )
         .arg(
             Arg::with_name("filename")
@@ -108,10 +168,193 @@ pub fn parse_command_line() -> App<'static, 'static> {
                 .global(true)
                 .takes_value(true)
                 .requires("output")
-                .help("Filename for '-o json' output"),
+                .help("Filename for '-o json', ndjson, yaml, toml, sqlite or parquet output"),
+        )
+        .arg(
+            Arg::with_name("append")
+                .long("append")
+                .global(true)
+                .takes_value(false)
+                .conflicts_with("overwrite")
+                .help("Add to the accounts of an existing output file"),
+        )
+        .arg(
+            Arg::with_name("overwrite")
+                .long("overwrite")
+                .global(true)
+                .takes_value(false)
+                .help("Replace an existing output file"),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .long("follow")
+                .value_name("DEPTH")
+                .global(true)
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value("0")
+                .help("Levels of PublicKey references to fetch and deserialize"),
+        )
+        .arg(
+            Arg::with_name("graph")
+                .long("graph")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["dot", "json"])
+                .help("Print the followed accounts as a graph instead of nested"),
+        )
+        .arg(
+            Arg::with_name("metadata")
+                .long("metadata")
+                .global(true)
+                .takes_value(false)
+                .help("Include the lamports, size, rent, executable flag and data hash of accounts"),
+        )
+        .arg(
+            Arg::with_name("fields")
+                .long("fields")
+                .value_name("NAMES")
+                .global(true)
+                .takes_value(true)
+                .use_delimiter(true)
+                .help("Comma separated top level items of the declaration to fetch and output"),
+        )
+        .subcommand(
+            App::new("account")
+                .about("Deserialize single account")
+                .arg(
+                    Arg::with_name("elf")
+                        .long("elf")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Write the ELF of an upgradeable program, program data or buffer account to a file"),
+                ),
+        )
+        .subcommand(
+            App::new("program")
+                .about("Deserialize all program owned accounts")
+                .arg(
+                    Arg::with_name("paged")
+                        .long("paged")
+                        .takes_value(false)
+                        .help("Scan for account keys, then fetch the accounts in batches"),
+                )
+                .arg(
+                    Arg::with_name("tui")
+                        .long("tui")
+                        .takes_value(false)
+                        .conflicts_with("graph")
+                        .help("Browse the accounts in the terminal instead of writing output"),
+                ),
+        )
+        .subcommand(
+            App::new("accounts")
+                .about("Deserialize accounts of many keys")
+                .arg(
+                    Arg::with_name("keys_file")
+                        .long("keys-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("File of Base58 keys, one per line [default: stdin]"),
+                ),
+        )
+        .subcommand(
+            App::new("sysvar").about("Deserialize a sysvar account").arg(
+                Arg::with_name("sysvar")
+                    .index(1)
+                    .value_name("NAME")
+                    .required(true)
+                    .possible_values(sysvar_names())
+                    .help("Sysvar name"),
+            ),
+        )
+        .subcommand(
+            App::new("instruction")
+                .about("Deserialize instruction data of a transaction or given data")
+                .arg(
+                    Arg::with_name("signature")
+                        .index(1)
+                        .value_name("SIGNATURE")
+                        .validator(is_parsable::<Signature>)
+                        .help("Transaction signature"),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .long("data")
+                        .takes_value(true)
+                        .conflicts_with("signature")
+                        .help("Instruction data"),
+                )
+                .arg(
+                    Arg::with_name("encoding")
+                        .long("encoding")
+                        .takes_value(true)
+                        .possible_values(&["base58", "base64"])
+                        .default_value("base58")
+                        .help("Encoding of '--data'"),
+                )
+                .group(
+                    ArgGroup::with_name("instruction_source")
+                        .args(&["signature", "data"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            App::new("logs")
+                .about("Deserialize events in program logs")
+                .arg(
+                    Arg::with_name("signature")
+                        .index(1)
+                        .value_name("SIGNATURE")
+                        .validator(is_parsable::<Signature>)
+                        .help("Transaction signature"),
+                )
+                .arg(
+                    Arg::with_name("logfile")
+                        .long("logfile")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("File of saved log lines"),
+                )
+                .arg(
+                    Arg::with_name("subscribe")
+                        .long("subscribe")
+                        .takes_value(false)
+                        .help("Subscribe to logs of transactions mentioning the program key"),
+                )
+                .group(
+                    ArgGroup::with_name("logs_source")
+                        .args(&["signature", "logfile", "subscribe"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            App::new("schema")
+                .about("Print the schema of the JSON output of a declaration")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["json-schema"])
+                        .default_value("json-schema")
+                        .help("Schema format"),
+                ),
+        )
+        .subcommand(
+            App::new("decl-schema")
+                .about("Print the JSON Schema of the declaration file format"),
+        )
+        .subcommand(
+            App::new("check")
+                .about("Check declaration files, or the given declaration, for errors")
+                .arg(
+                    Arg::with_name("files")
+                        .index(1)
+                        .value_name("FILE")
+                        .multiple(true)
+                        .help("Declaration files [default: the '-d' or '-l' declaration]"),
+                ),
         )
-        .subcommand(App::new("account").about("Deserialize single account"))
-        .subcommand(App::new("program").about("Deserialize all program owned accounts"))
         .group(
             ArgGroup::with_name("key_flags").args(&["keypair", "pkstr", "sampkey"]), // .required(true),
         )
@@ -127,10 +370,7 @@ lazy_static! {
                 "prog",
                 "../../samples/keys/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.json",
             );
-            jump_table.insert(
-                "decl",
-                "../../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml",
-            );
+            jump_table.insert("registry", "../../samples/yamldecls");
             jump_table
         } else {
             jump_table.insert("user1", "../samples/keys/user1_account.json");
@@ -139,19 +379,123 @@ lazy_static! {
                 "prog",
                 "../samples/keys/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.json",
             );
-            jump_table.insert(
-                "decl",
-                "../samples/yamldecls/SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv.yml",
-            );
+            jump_table.insert("registry", "../samples/yamldecls");
             jump_table
         }
     };
 }
 
+/// Get the declaration from either the bundled layout
+/// name or the declaration file path, None if neither
+/// is given and the registry is used
+pub fn get_declaration(matches: &ArgMatches) -> Option<Vec<Yaml>> {
+    if let Some(layout_name) = matches.value_of("layout") {
+        return layout(layout_name);
+    }
+    let descriptor_file_name = matches.value_of("decl")?;
+    let indecl = load_yaml_file(descriptor_file_name);
+    if indecl.is_err() {
+        eprintln!(
+            "File error: On {} {}",
+            descriptor_file_name,
+            indecl.err().unwrap()
+        );
+        exit(1)
+    } else {
+        indecl.ok()
+    }
+}
+
+/// Get the declaration registry, defaulting to the sample
+/// declarations when a sample key is used
+pub fn get_registry(matches: &ArgMatches) -> SadRegistry {
+    match matches.value_of("registry") {
+        Some(dir) => SadRegistry::new(Some(dir)),
+        None if matches.is_present("sampkey") => {
+            SadRegistry::new(SAMPLE_KEYS_MAP.get("registry").copied())
+        }
+        None => SadRegistry::new(None),
+    }
+}
+
+/// Get the instruction declaration registry
+pub fn get_instruction_registry(matches: &ArgMatches) -> SadRegistry {
+    SadRegistry::for_instructions(matches.value_of("registry"))
+}
+
+/// Get the event declaration registry
+pub fn get_event_registry(matches: &ArgMatches) -> SadRegistry {
+    SadRegistry::for_events(matches.value_of("registry"))
+}
+
+/// Get the program key, if any, and provided descriptor, if any,
+/// for instruction and event deserialization
+pub fn get_program_and_descriptor(matches: &ArgMatches) -> (Option<Pubkey>, Option<Vec<Yaml>>) {
+    if matches.is_present("keypair") || matches.is_present("pkstr") || matches.is_present("sampkey")
+    {
+        let (kp, indecl) = get_account_and_descriptor(matches);
+        (Some(kp), indecl)
+    } else {
+        (None, get_declaration(matches))
+    }
+}
+
+/// Get the '--data' instruction data, decoded from its encoding
+pub fn get_instruction_data(matches: &ArgMatches) -> Option<Vec<u8>> {
+    let data = matches.value_of("data")?;
+    let decoded = match matches.value_of("encoding").unwrap() {
+        "base64" => base64::decode(data).map_err(|e| e.to_string()),
+        _ => bs58::decode(data).into_vec().map_err(|e| e.to_string()),
+    };
+    match decoded {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            eprintln!("Data error: On {} {}", data, e);
+            exit(1)
+        }
+    }
+}
+
+/// Parse Base58 keys, one per line, skipping blank
+/// lines and '#' comments
+fn parse_keys(text: &str) -> Result<Vec<Pubkey>, String> {
+    let mut keys = Vec::<Pubkey>::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match Pubkey::from_str(line) {
+            Ok(key) => keys.push(key),
+            Err(e) => return Err(format!("line {} {} {}", index + 1, line, e)),
+        }
+    }
+    Ok(keys)
+}
+
+/// Get the keys from the '--keys-file' or stdin and
+/// provided descriptor, if any
+pub fn get_keys_and_descriptor(matches: &ArgMatches) -> (Vec<Pubkey>, Option<Vec<Yaml>>) {
+    let file_name = matches.value_of("keys_file").unwrap_or("-");
+    let text = if file_name == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        read_to_string(file_name)
+    };
+    let keys = text
+        .map_err(|e| e.to_string())
+        .and_then(|t| parse_keys(&t))
+        .unwrap_or_else(|err| {
+            eprintln!("Key error: On {} {}", file_name, err);
+            exit(1)
+        });
+    (keys, get_declaration(matches))
+}
+
 /// Get correct public key from command line and
-/// associated sample descriptor or provided descriptor
-/// path
-pub fn get_account_and_descriptor(matches: &ArgMatches) -> (Pubkey, Vec<Yaml>) {
+/// provided descriptor, if any
+pub fn get_account_and_descriptor(matches: &ArgMatches) -> (Pubkey, Option<Vec<Yaml>>) {
     let (kp, ks, ss) = (
         matches.is_present("keypair"),
         matches.is_present("pkstr"),
@@ -159,36 +503,14 @@ pub fn get_account_and_descriptor(matches: &ArgMatches) -> (Pubkey, Vec<Yaml>) {
     );
     match (kp, ks, ss) {
         (true, _, _) => {
-            let descriptor_file_name = matches.value_of("decl").unwrap();
             let kp = read_keypair_file(matches.value_of("keypair").unwrap())
                 .unwrap()
                 .pubkey();
-            let indecl = load_yaml_file(descriptor_file_name);
-            if indecl.is_err() {
-                eprintln!(
-                    "File error: On {} {}",
-                    descriptor_file_name,
-                    indecl.err().unwrap()
-                );
-                exit(1)
-            } else {
-                (kp, indecl.unwrap())
-            }
+            (kp, get_declaration(matches))
         }
         (_, true, _) => {
-            let descriptor_file_name = matches.value_of("decl").unwrap();
             let kp = Pubkey::from_str(matches.value_of("pkstr").unwrap()).unwrap();
-            let indecl = load_yaml_file(descriptor_file_name);
-            if indecl.is_err() {
-                eprintln!(
-                    "File error: On {} {}",
-                    descriptor_file_name,
-                    indecl.err().unwrap()
-                );
-                exit(1)
-            } else {
-                (kp, indecl.unwrap())
-            }
+            (kp, get_declaration(matches))
         }
         (_, _, true) => {
             let kp = read_keypair_file(
@@ -198,18 +520,7 @@ pub fn get_account_and_descriptor(matches: &ArgMatches) -> (Pubkey, Vec<Yaml>) {
             )
             .unwrap()
             .pubkey();
-            let descriptor_file_name = *SAMPLE_KEYS_MAP.get("decl").unwrap();
-            let indecl = load_yaml_file(descriptor_file_name);
-            if indecl.is_err() {
-                eprintln!(
-                    "File error: On {} {}",
-                    descriptor_file_name,
-                    indecl.err().unwrap()
-                );
-                exit(1)
-            } else {
-                (kp, indecl.unwrap())
-            }
+            (kp, get_declaration(matches))
         }
         _ => unreachable!(),
     }
@@ -323,4 +634,23 @@ mod tests {
         let matches = keysetup(vec!["prog"]);
         assert!(matches.is_err());
     }
+    #[test]
+    fn test_parse_keys_pass() {
+        let keys = parse_keys(
+            "# keys\nSampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv\n\n  11111111111111111111111111111111  \n",
+        )
+        .unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                Pubkey::from_str("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv").unwrap(),
+                Pubkey::default()
+            ]
+        );
+    }
+    #[test]
+    fn test_parse_keys_fail() {
+        let err = parse_keys("11111111111111111111111111111111\nnot_a_key\n").unwrap_err();
+        assert!(err.starts_with("line 2 not_a_key"));
+    }
 }
@@ -1,51 +1,35 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
-pub enum SadTreeError {
-    #[error("Don't know type {0}")]
-    UnknownType(String),
-    #[error("Expected 'type:' as first entry, found {0}")]
-    ExpectedTypeKeyError(String),
-    #[error("Expected YAML HashMap")]
-    ExpectedHashMap,
-    #[error("Expected YAML Array")]
-    ExpectedArray,
-    #[error("Expected YAML HashMap or Array")]
-    ExpectedHashMapOrArray,
-    #[error("Expected HashMap fields")]
-    ExpectedHashMapFields,
-    #[error("Expected Vec contains")]
-    ExpectedVecContains,
-    #[error("Expected Tuple fields")]
-    ExpectedTupleFields,
-    #[error("Expected CStruct fields")]
-    ExpectedCStructFields,
-    #[error("Expected Length Prefix Schema Ancillary Type")]
-    ExpectedLengthSchemaType,
-}
-
-#[derive(Error, Debug)]
-pub enum SadAccountErrorType {
-    #[error("Failed getting Account from cluster")]
-    FailedAccountGet,
-    #[error("Failed getting Program Accounts from cluster")]
-    FailedProgramAccountGet,
-    #[error("Could not resolve Solana config")]
-    ConfigFileError,
-    #[error("RcpClient creation failed")]
-    RpcSetupFail,
-    #[error("Account key is an executable account")]
-    AccountIsExecutableError,
-    #[error("Not a valid Program key")]
-    NotProgramKeyError,
-}
-
 #[derive(Error, Debug)]
 pub enum SadAppErrorType {
     #[error("Row expected {0} elements. found {1}")]
     InconsistentRowLength(usize, usize),
+    #[error("Output file {0} exists, use '--append' or '--overwrite'")]
+    OutputFileExists(String),
+    #[error("Output file {0} is not a JSON array to append to")]
+    ExpectedJsonArrayFile(String),
+    #[error("Failed writing output: {0}")]
+    OutputWriteError(#[from] std::io::Error),
+    #[error("{0} output holds accounts, not events")]
+    AccountsOnlyOutput(String),
+    #[error("Failed writing SQLite output: {0}")]
+    SqliteWriteError(#[from] rusqlite::Error),
+    #[error("{0} output can't be appended to")]
+    AppendUnsupported(String),
+    #[error("Parquet output holds accounts of one declaration, found {0} and {1}")]
+    MixedDeclarations(String, String),
+    #[error("Failed converting to Arrow: {0}")]
+    ArrowConversionError(#[from] arrow_schema::ArrowError),
+    #[error("Failed writing Parquet output: {0}")]
+    ParquetWriteError(#[from] parquet::errors::ParquetError),
+    #[error("A declaration or layout is required for a schema")]
+    ExpectedSchemaDeclaration,
+    #[error("A declaration file or layout is required to check")]
+    ExpectedCheckDeclaration,
+    #[error("{0} declaration documents failed the check")]
+    FailedCheck(usize),
+    #[error("Failed converting deserialized data: {0}")]
+    DataConversionError(#[from] gadgets_sad::errors::SadTreeError),
 }
 
-pub type SadTreeResult<T> = std::result::Result<T, SadTreeError>;
-pub type SadAccountResult<T> = std::result::Result<T, SadAccountErrorType>;
 pub type SadApplicationResult<T> = std::result::Result<T, SadAppErrorType>;
@@ -1,26 +1,242 @@
 //! @brief Main entry poiint for CLI
 
-use clparse::get_account_and_descriptor;
+use clparse::{
+    get_account_and_descriptor, get_declaration, get_event_registry, get_instruction_data,
+    get_instruction_registry, get_keys_and_descriptor, get_program_and_descriptor, get_registry,
+};
 
 use {
-    desertree::Deseriaizer,
-    sadout::{SadJsonOutput, SadOutput, SadSysOutput},
+    clap::{value_t, ArgMatches},
+    errors::SadAppErrorType,
+    gadgets_common::load_yaml_file,
+    gadgets_sad::{
+        desertree::declaration_json_schema,
+        instq, library, loaderq, logq,
+        solq::{self, AccountResultContext, AccountSink, DeserializationResult},
+        DeclarationSelector, Deseriaizer, SadAccountErrorType, SadDeclarationSet,
+    },
+    sadcheck::SadDeclarationCheck,
+    sadout::{
+        result_json_schema, GraphFormat, OutputFileMode, SadGraphOutput, SadJsonOutput,
+        SadNdjsonOutput, SadOutput, SadParquetOutput, SadSqliteOutput, SadStreamOutput,
+        SadSysOutput, SadTomlOutput, SadTreeOutput, SadYamlOutput,
+    },
+    serde_json::{json, to_string_pretty},
     solana_clap_utils::{input_validators::normalize_to_url_if_moniker, keypair::DefaultSigner},
     solana_client::rpc_client::RpcClient,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
-    solana_sdk::{commitment_config::CommitmentConfig, signature::Signer},
-    std::{process::exit, sync::Arc},
+    solana_sdk::{
+        clock::Slot,
+        commitment_config::CommitmentConfig,
+        signature::{Signature, Signer},
+    },
+    std::{error::Error, process::exit, rc::Rc, str::FromStr, sync::Arc},
+    yaml_rust::Yaml,
 };
 
 /// sad main module
 mod clparse;
-mod desertree;
 mod errors;
+mod sadcheck;
 mod sadout;
-mod sadtypes;
-mod solq;
+mod sadtui;
+
+/// What is done with an existing output file
+fn output_file_mode(matches: &ArgMatches) -> OutputFileMode {
+    if matches.is_present("append") {
+        OutputFileMode::Append
+    } else if matches.is_present("overwrite") {
+        OutputFileMode::Overwrite
+    } else {
+        OutputFileMode::Create
+    }
+}
+
+/// Writes deserialization results to the selected output
+fn write_result(matches: &ArgMatches, result: DeserializationResult) -> Result<(), Box<dyn Error>> {
+    write_output(matches, result, output_file_mode(matches))
+}
+
+/// Writes deserialization results to the selected output, with
+/// the mode for an existing output file
+fn write_output(
+    matches: &ArgMatches,
+    result: DeserializationResult,
+    mode: OutputFileMode,
+) -> Result<(), Box<dyn Error>> {
+    let metadata = matches.is_present("metadata");
+    match matches.value_of("output").unwrap() {
+        "json" => SadJsonOutput::new(result, matches.value_of("filename").unwrap())
+            .with_metadata(metadata)
+            .with_mode(mode)
+            .write()?,
+        "yaml" => SadYamlOutput::new(result, matches.value_of("filename"))
+            .with_metadata(metadata)
+            .with_mode(mode)
+            .write()?,
+        "toml" => SadTomlOutput::new(result, matches.value_of("filename"))
+            .with_metadata(metadata)
+            .with_mode(mode)
+            .write()?,
+        "ndjson" => SadNdjsonOutput::new(matches.value_of("filename"), mode)?
+            .with_metadata(metadata)
+            .write_result(&result)?,
+        "sqlite" => {
+            SadSqliteOutput::new(matches.value_of("filename").unwrap())?.write_result(&result)?
+        }
+        "tree" => SadTreeOutput::new(result)
+            .with_metadata(metadata)
+            .with_color(!matches.is_present("no_color"))
+            .write()?,
+        "stdout" => SadSysOutput::new(result).with_metadata(metadata).write()?,
+        _ => unreachable!(),
+    };
+    Ok(())
+}
+
+/// Writer of accounts as they are deserialized, for '-o ndjson',
+/// '-o sqlite' and '-o parquet' when references are not followed
+/// and the accounts are not browsed
+fn account_stream(
+    matches: &ArgMatches,
+) -> Result<Option<Box<dyn SadStreamOutput>>, Box<dyn Error>> {
+    let depth = value_t!(matches, "follow", usize).unwrap_or(0);
+    if depth > 0 || matches.is_present("graph") || matches.is_present("tui") {
+        return Ok(None);
+    }
+    Ok(match matches.value_of("output") {
+        Some("ndjson") => Some(Box::new(
+            SadNdjsonOutput::new(matches.value_of("filename"), output_file_mode(matches))?
+                .with_metadata(matches.is_present("metadata")),
+        )),
+        Some("sqlite") => Some(Box::new(SadSqliteOutput::new(
+            matches.value_of("filename").unwrap(),
+        )?)),
+        Some("parquet") => Some(Box::new(SadParquetOutput::new(
+            matches.value_of("filename").unwrap(),
+            output_file_mode(matches),
+        )?)),
+        _ => None,
+    })
+}
+
+/// Writes a streamed account, exiting when output fails
+fn write_streamed(
+    stream: Option<&mut Box<dyn SadStreamOutput>>,
+    context: &AccountResultContext,
+    slot: Slot,
+) {
+    if let Err(err) = stream.unwrap().write_context(context, slot) {
+        eprintln!("Output error: {}", err);
+        exit(1);
+    }
+}
+
+/// Completes the streamed output with the rest of the result, or
+/// writes the result to the selected output
+fn finish_result(
+    matches: &ArgMatches,
+    stream: Option<Box<dyn SadStreamOutput>>,
+    result: DeserializationResult,
+) -> Result<(), Box<dyn Error>> {
+    match stream {
+        Some(mut stream) => Ok(stream.write_result(&result)?),
+        None => write_result(matches, result),
+    }
+}
+
+/// Deserialization tree of a declaration, restricted to the
+/// '--fields' items when given
+fn declaration(matches: &ArgMatches, decl: &Yaml) -> Result<Deseriaizer, Box<dyn Error>> {
+    let destree = Deseriaizer::new(decl);
+    match matches.values_of("fields") {
+        Some(fields) => Ok(destree.with_fields(&fields.map(String::from).collect::<Vec<_>>())?),
+        None => Ok(destree),
+    }
+}
+
+/// Selector of a declaration, or of the registry when none is given
+fn account_selector(
+    matches: &ArgMatches,
+    indecl: Option<Vec<Yaml>>,
+) -> Result<Box<dyn DeclarationSelector>, Box<dyn Error>> {
+    match indecl {
+        Some(decl) => Ok(Box::new(Rc::new(declaration(matches, &decl[0])?))),
+        None if matches.is_present("fields") => {
+            Err(Box::new(SadAccountErrorType::ExpectedDeclarationError))
+        }
+        None => Ok(Box::new(get_registry(matches))),
+    }
+}
+
+/// Writes the schema of the accounts '-o json' writes for the given
+/// declaration, to the '--filename' file or stdout
+fn write_schema(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let decl = get_declaration(matches).ok_or(SadAppErrorType::ExpectedSchemaDeclaration)?;
+    let schema = match matches.value_of("format").unwrap() {
+        "json-schema" => result_json_schema(
+            &declaration(matches, &decl[0])?,
+            matches.is_present("metadata"),
+            value_t!(matches, "follow", usize).unwrap_or(0) > 0,
+        ),
+        _ => unreachable!(),
+    };
+    write_json(matches, &schema)
+}
+
+/// Writes pretty JSON to the '--filename' file or stdout
+fn write_json(matches: &ArgMatches, value: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+    match matches.value_of("filename") {
+        Some(file_name) => std::fs::write(file_name, to_string_pretty(value)?)?,
+        None => println!("{}", to_string_pretty(value)?),
+    }
+    Ok(())
+}
+
+/// Checks the documents of the declaration files, or of the '-d' or
+/// '-l' declaration, printing the problems found with each
+fn check_declarations(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let sources = match matches.values_of("files") {
+        Some(files) => files
+            .map(|f| (f.to_string(), load_yaml_file(f).map_err(|e| e.to_string())))
+            .collect::<Vec<_>>(),
+        None => {
+            let source = matches
+                .value_of("decl")
+                .or_else(|| matches.value_of("layout"))
+                .ok_or(SadAppErrorType::ExpectedCheckDeclaration)?;
+            vec![(source.to_string(), Ok(get_declaration(matches).unwrap()))]
+        }
+    };
+    let check = SadDeclarationCheck::new();
+    let mut failed = 0;
+    for (source, docs) in sources {
+        let docs = match docs {
+            Ok(docs) => docs,
+            Err(err) => {
+                println!("{}: {}", source, err);
+                failed += 1;
+                continue;
+            }
+        };
+        for (index, doc) in docs.iter().enumerate() {
+            let problems = check.problems(doc);
+            if problems.is_empty() {
+                println!("{}[{}]: ok", source, index);
+            } else {
+                failed += 1;
+                for problem in problems {
+                    println!("{}[{}] {}", source, index, problem);
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(Box::new(SadAppErrorType::FailedCheck(failed)));
+    }
+    Ok(())
+}
 
-#[allow(dead_code)]
 struct Config {
     commitment_config: CommitmentConfig,
     default_signer: Box<dyn Signer>,
@@ -37,6 +253,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("");
         exit(-1);
     });
+    // Schemas and checks are of declarations alone, without a cluster
+    if sub_command == "schema" {
+        return write_schema(matches);
+    }
+    if sub_command == "decl-schema" {
+        return write_json(matches, &declaration_json_schema());
+    }
+    if sub_command == "check" {
+        return check_declarations(matches);
+    }
     let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
     let config = {
         let cli_config = if let Some(config_file) = matches.value_of("config_file") {
@@ -62,7 +288,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     exit(1);
                 }),
             verbose: matches.is_present("verbose"),
-            commitment_config: CommitmentConfig::confirmed(),
+            commitment_config: CommitmentConfig::from_str(matches.value_of("commitment").unwrap())
+                .unwrap(),
         }
     };
     // Change to "solana=debug" if needed
@@ -70,11 +297,147 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if config.verbose {
         println!("JSON RPC URL: {}", config.json_rpc_url);
+        println!("Default signer: {}", config.default_signer.pubkey());
     }
-    let rpc_client = RpcClient::new(config.json_rpc_url.clone());
+    let rpc_client =
+        RpcClient::new_with_commitment(config.json_rpc_url.clone(), config.commitment_config);
+    let min_context_slot = value_t!(matches, "min_context_slot", Slot).ok();
 
     // Arguments specific to deserialization
 
+    // Instructions are selected by program rather than account
+    if sub_command == "instruction" {
+        let (program_key, indecl) = get_program_and_descriptor(matches);
+        let single_declaration = indecl.is_some();
+        let selector: Box<dyn DeclarationSelector> = match indecl {
+            Some(decl) => Box::new(Rc::new(Deseriaizer::new(&decl[0]))),
+            None => Box::new(get_instruction_registry(matches)),
+        };
+        let decoded = match get_instruction_data(matches) {
+            Some(data) => vec![instq::decode_instruction_data(
+                data,
+                program_key,
+                &*selector,
+            )?],
+            None => {
+                if single_declaration && program_key.is_none() {
+                    return Err(Box::new(SadAccountErrorType::ExpectedProgramKeyError));
+                }
+                let signature = value_t!(matches, "signature", Signature).unwrap();
+                instq::decode_transaction(
+                    &rpc_client,
+                    &signature,
+                    program_key.as_ref(),
+                    &*selector,
+                    min_context_slot,
+                )?
+            }
+        };
+        let json_out = json!(decoded
+            .iter()
+            .map(|d| d.to_json())
+            .collect::<Result<Vec<_>, _>>()?);
+        match matches.value_of("output").unwrap() {
+            "json" => std::fs::write(
+                matches.value_of("filename").unwrap(),
+                to_string_pretty(&json_out)?,
+            )?,
+            // Instructions print as pretty JSON
+            "tree" | "stdout" => println!("{}", to_string_pretty(&json_out)?),
+            _ => unreachable!(),
+        };
+        return Ok(());
+    }
+
+    // Events are selected by program and discriminator
+    if sub_command == "logs" {
+        let (program_key, indecl) = get_program_and_descriptor(matches);
+        let selector: Box<dyn DeclarationSelector> = match indecl {
+            Some(decl) => Box::new(SadDeclarationSet::new(decl)),
+            None => Box::new(get_event_registry(matches)),
+        };
+        if matches.is_present("subscribe") {
+            let websocket_url =
+                solana_cli_config::Config::compute_websocket_url(&config.json_rpc_url);
+            let (_subscription, receiver) = logq::subscribe_logs(
+                &websocket_url,
+                program_key.as_ref(),
+                config.commitment_config,
+            )?;
+            let mut written = false;
+            for response in receiver.iter() {
+                let signature = Signature::from_str(&response.value.signature).ok();
+                let events = logq::decode_events(
+                    signature,
+                    Some(response.context.slot),
+                    &response.value.logs,
+                    program_key.as_ref(),
+                    &*selector,
+                );
+                if !events.is_empty() {
+                    // Later events are added to the file of the first
+                    let mode = if written {
+                        OutputFileMode::Append
+                    } else {
+                        output_file_mode(matches)
+                    };
+                    write_output(matches, DeserializationResult::from_events(events), mode)?;
+                    written = true;
+                }
+            }
+            return Ok(());
+        }
+        let (signature, slot, logs) = match matches.value_of("logfile") {
+            Some(file_name) => (
+                None,
+                None,
+                logq::file_logs(file_name).unwrap_or_else(|err| {
+                    eprintln!("File error: On {} {}", file_name, err);
+                    exit(1);
+                }),
+            ),
+            None => {
+                let signature = value_t!(matches, "signature", Signature).unwrap();
+                let (slot, logs) =
+                    logq::transaction_logs(&rpc_client, &signature, min_context_slot)?;
+                (Some(signature), Some(slot), logs)
+            }
+        };
+        let events = logq::decode_events(signature, slot, &logs, program_key.as_ref(), &*selector);
+        return write_result(matches, DeserializationResult::from_events(events));
+    }
+
+    // Sysvars are selected by name, with their bundled layout
+    if sub_command == "sysvar" {
+        let (sysvar_key, layout_name) =
+            library::sysvar(matches.value_of("sysvar").unwrap()).unwrap();
+        let destree = Rc::new(declaration(
+            matches,
+            &library::layout(layout_name).unwrap()[0],
+        )?);
+        let result =
+            solq::deserialize_account(&rpc_client, &sysvar_key, &destree, min_context_slot)?;
+        return write_result(matches, result);
+    }
+
+    // Many accounts, fetched in batches
+    if sub_command == "accounts" {
+        let (keys, indecl) = get_keys_and_descriptor(matches);
+        let selector = account_selector(matches, indecl)?;
+        let mut stream = account_stream(matches)?;
+        let streaming = stream.is_some();
+        let mut write_context =
+            |c: &AccountResultContext, slot| write_streamed(stream.as_mut(), c, slot);
+        let sink: AccountSink = if streaming {
+            Some(&mut write_context)
+        } else {
+            None
+        };
+        let result =
+            solq::deserialize_accounts(&rpc_client, &keys, &*selector, min_context_slot, sink)?;
+        return finish_result(matches, stream, result);
+    }
+
     // Setup the account or program public key
     let (target_pubkey, indecl) = get_account_and_descriptor(matches);
     // let target_pubkey = get_target_publickey(matches);
@@ -86,25 +449,72 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     //     exit(1);
     // });
 
-    // Setup the deserialization tree
-    let destree = Deseriaizer::new(&indecl[0]);
+    // Setup the deserialization tree, or the registry to select one by owner
+    let selector = account_selector(matches, indecl)?;
 
-    // Get deserialization results
-    let deserialize_result = match sub_command {
-        "account" => solq::deserialize_account(&rpc_client, &target_pubkey, &destree)?,
-        "program" => solq::deserialize_program_accounts(&rpc_client, &target_pubkey, &destree)?,
-        _ => unreachable!(),
+    // Program accounts may be written as they are deserialized
+    let mut stream = account_stream(matches)?;
+    let streaming = stream.is_some();
+    let mut write_context =
+        |c: &AccountResultContext, slot| write_streamed(stream.as_mut(), c, slot);
+    let sink: AccountSink = if streaming {
+        Some(&mut write_context)
+    } else {
+        None
     };
-    // Check for output or default to pretty print
-    match matches.value_of("output").unwrap() {
-        "json" => SadJsonOutput::new(
-            deserialize_result,
-            destree,
-            matches.value_of("filename").unwrap(),
-        )
-        .write()?,
-        "stdout" => SadSysOutput::new(deserialize_result, destree).write()?,
+
+    // Get deserialization results
+    let mut deserialize_result = match sub_command {
+        "account" => {
+            solq::deserialize_account(&rpc_client, &target_pubkey, &*selector, min_context_slot)?
+        }
+        "program" => solq::deserialize_program_accounts(
+            &rpc_client,
+            &target_pubkey,
+            &*selector,
+            min_context_slot,
+            matches.is_present("paged"),
+            sink,
+        )?,
         _ => unreachable!(),
     };
-    Ok(())
+    // Extract the program ELF of an upgradeable loader account
+    if let Some(file_name) = matches.value_of("elf") {
+        let context = &deserialize_result.context_vec()[0];
+        let elf = loaderq::program_elf(
+            &rpc_client,
+            context.pubkey(),
+            context.account(),
+            deserialize_result.context_slot(),
+        )?;
+        let elf_hash = loaderq::write_elf(file_name, &elf)?;
+        eprintln!("Wrote ELF to {} sha256 {}", file_name, elf_hash);
+    }
+    // Follow references, selecting declarations by owner
+    let depth = value_t!(matches, "follow", usize).unwrap_or(0);
+    if depth > 0 {
+        solq::follow_references(
+            &rpc_client,
+            &mut deserialize_result,
+            &get_registry(matches),
+            depth,
+        )?;
+    }
+    // Browse the accounts in the terminal
+    if matches.is_present("tui") {
+        return Ok(sadtui::browse(&deserialize_result)?);
+    }
+    // Check for graph, output or default to pretty print
+    if let Some(format) = matches.value_of("graph") {
+        let format = match format {
+            "dot" => GraphFormat::Dot,
+            "json" => GraphFormat::Json,
+            _ => unreachable!(),
+        };
+        SadGraphOutput::new(deserialize_result, format)
+            .with_metadata(matches.is_present("metadata"))
+            .write()?;
+        return Ok(());
+    }
+    finish_result(matches, stream, deserialize_result)
 }
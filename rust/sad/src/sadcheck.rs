@@ -0,0 +1,91 @@
+//! @brief Declaration checks
+//! Validates declaration documents against the JSON Schema of the
+//! declaration language, then parses those that are well formed
+
+use {
+    gadgets_sad::{
+        desertree::{declaration_json, declaration_json_schema},
+        Deseriaizer,
+    },
+    jsonschema::Validator,
+    yaml_rust::Yaml,
+};
+
+/// Checks declaration documents, structurally and then by parsing them
+pub struct SadDeclarationCheck {
+    validator: Validator,
+}
+
+impl SadDeclarationCheck {
+    pub fn new() -> Self {
+        Self {
+            validator: jsonschema::validator_for(&declaration_json_schema()).unwrap(),
+        }
+    }
+
+    /// Problems with a declaration document, each with the path to
+    /// where it is found. Parsing errors are reported only for
+    /// documents without structural problems
+    pub fn problems(&self, doc: &Yaml) -> Vec<String> {
+        let problems = self
+            .validator
+            .iter_errors(&declaration_json(doc))
+            .map(|e| format!("{}: {}", instance_path(&e.instance_path.to_string()), e))
+            .collect::<Vec<_>>();
+        if !problems.is_empty() {
+            return problems;
+        }
+        match Deseriaizer::try_new(doc) {
+            Ok(_) => problems,
+            Err(e) => vec![format!("/: {}", e)],
+        }
+    }
+}
+
+/// Path of a value in a document, '/' for the document itself
+fn instance_path(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use gadgets_sad::library::{layout, layout_names};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_check_pass() {
+        let check = SadDeclarationCheck::new();
+        for name in layout_names() {
+            for doc in layout(name).unwrap() {
+                assert_eq!(check.problems(&doc), Vec::<String>::new(), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_fail() {
+        let check = SadDeclarationCheck::new();
+        let docs = YamlLoader::load_from_str(
+            "bad:\n  - a:\n      type: Vec\n      contains:\n        - type: U256\n---\nbad:\n  - a:\n      $ref: Missing\n",
+        )
+        .unwrap();
+        let problems = check.problems(&docs[0]);
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].starts_with("/bad/0/a/contains/0/type: "),
+            "{}",
+            problems[0]
+        );
+        // Well formed declarations may still fail to parse
+        assert_eq!(
+            check.problems(&docs[1]),
+            vec!["/: Reference to undefined type Missing".to_string()]
+        );
+    }
+}
@@ -1,11 +1,26 @@
 //! @brief sad outputs
 
-use crate::{desertree::Deseriaizer, errors::SadApplicationResult, solq::DeserializationResult};
-use serde_json::{from_str, json, to_string_pretty};
+use crate::errors::{SadAppErrorType, SadApplicationResult};
+use arrow_json::{reader::Decoder, ReaderBuilder};
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use crossterm::style::{Color, Stylize};
+use gadgets_sad::{
+    loaderq::sha256_hex,
+    solq::{AccountResultContext, DeserializationResult, EventResultContext, ResultForKeyType},
+    Deseriaizer, SadSchema, SadSchemaElement,
+};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection};
+use serde_json::{json, to_string, to_string_pretty, Value};
+use solana_sdk::{clock::Slot, native_token::lamports_to_sol, pubkey::Pubkey, rent::Rent};
 use std::{
-    fs::{read_to_string, File},
-    io::Write,
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Arc,
 };
+use yaml_rust::{Yaml, YamlEmitter};
 
 /// Simple trait for
 pub trait SadOutput: std::fmt::Debug {
@@ -14,46 +29,297 @@ pub trait SadOutput: std::fmt::Debug {
     fn write(&self) -> SadApplicationResult<()>;
 }
 
+/// Output written an account at a time, as accounts are deserialized
+pub trait SadStreamOutput {
+    /// Writes an account, with the slot it was read at
+    fn write_context(
+        &mut self,
+        context: &AccountResultContext,
+        slot: Slot,
+    ) -> SadApplicationResult<()>;
+    /// Writes what a result holds, completing the output
+    fn write_result(&mut self, result: &DeserializationResult) -> SadApplicationResult<()>;
+}
+
+/// JSON of the metadata of a deserialized account. The size, rent and
+/// hash of data fetched as a slice are unknown and null. The rent
+/// exempt minimum is that of the default rent
+fn metadata_json(context: &AccountResultContext) -> Value {
+    let account = context.account();
+    let data_len = (!context.is_sliced()).then_some(account.data.len());
+    let rent = Rent::default();
+    json!({
+        "lamports": account.lamports,
+        "sol": lamports_to_sol(account.lamports),
+        "data_len": data_len,
+        "rent_exempt_minimum": data_len.map(|l| rent.minimum_balance(l)),
+        "rent_exempt": data_len.map(|l| rent.is_exempt(account.lamports, l)),
+        "executable": account.executable,
+        "rent_epoch": account.rent_epoch,
+        "data_sha256": data_len.map(|_| sha256_hex(&account.data)),
+    })
+}
+
+/// JSON of a deserialized account, with its metadata under 'account'
+/// when selected
+fn context_json(context: &AccountResultContext, metadata: bool) -> SadApplicationResult<Value> {
+    let mut jmap = json!({});
+    let jmap_raw = jmap.as_object_mut().unwrap();
+    jmap_raw.insert(
+        "account_key".to_string(),
+        json!(context.pubkey().to_string()),
+    );
+    jmap_raw.insert(
+        "account_program_key".to_string(),
+        json!(context.account().owner.to_string()),
+    );
+    if metadata {
+        jmap_raw.insert("account".to_string(), metadata_json(context));
+    }
+    jmap_raw.insert("data".to_string(), context.to_json()?);
+    Ok(jmap)
+}
+
+/// JSON of a deserialized account, with the accounts it references
+/// nested under 'references' when references were followed
+fn account_json(
+    result: &DeserializationResult,
+    context: &AccountResultContext,
+    metadata: bool,
+    visiting: &mut Vec<Pubkey>,
+) -> SadApplicationResult<Value> {
+    let mut jmap = context_json(context, metadata)?;
+    let jmap_raw = jmap.as_object_mut().unwrap();
+    if result.is_followed() {
+        visiting.push(*context.pubkey());
+        let references = result
+            .references()
+            .iter()
+            .filter(|r| r.from() == context.pubkey())
+            .map(|r| {
+                let mut linked = match result.context_for(r.to()) {
+                    Some(c) if !visiting.contains(r.to()) => {
+                        account_json(result, c, metadata, visiting)?
+                    }
+                    _ => unresolved_json(result, r.to()),
+                };
+                linked
+                    .as_object_mut()
+                    .unwrap()
+                    .insert("field".to_string(), json!(r.field()));
+                Ok(linked)
+            })
+            .collect::<SadApplicationResult<Vec<Value>>>()?;
+        visiting.pop();
+        jmap_raw.insert("references".to_string(), json!(references));
+    }
+    Ok(jmap)
+}
+
+/// JSON of a referenced account that is not deserialized, either not
+/// followed, not found or without a matching declaration
+fn unresolved_json(result: &DeserializationResult, key: &Pubkey) -> Value {
+    let owner = match result.context_for(key) {
+        Some(c) => Some(c.account().owner),
+        None => result.unresolved().get(key).copied().flatten(),
+    };
+    json!({
+        "account_key": key.to_string(),
+        "account_program_key": owner.map(|o| o.to_string()),
+    })
+}
+
+/// JSON of a requested account that is not deserialized, with
+/// the reason and the deserialization error, if any
+fn missing_json(result: &DeserializationResult, key: &Pubkey) -> Value {
+    let mut jmap = unresolved_json(result, key);
+    let jmap_raw = jmap.as_object_mut().unwrap();
+    jmap_raw.insert("error".to_string(), json!(missing_reason(result, key)));
+    if let Some(detail) = result.failed().get(key) {
+        jmap_raw.insert("detail".to_string(), json!(detail));
+    }
+    jmap
+}
+
+/// Reasons a requested account is not deserialized
+const NO_DECLARATION_MATCHES: &str = "No declaration matches account";
+const ACCOUNT_NOT_FOUND: &str = "Account not found";
+const DESERIALIZATION_FAILED: &str = "Account data does not fit its declaration";
+
+/// Why a requested account is not deserialized
+fn missing_reason(result: &DeserializationResult, key: &Pubkey) -> &'static str {
+    if result.failed().contains_key(key) {
+        return DESERIALIZATION_FAILED;
+    }
+    match result.unresolved().get(key) {
+        Some(Some(_)) => NO_DECLARATION_MATCHES,
+        _ => ACCOUNT_NOT_FOUND,
+    }
+}
+
+/// JSON of an event deserialized from program logs, with the slot it
+/// was read at when known
+fn event_json(context: &EventResultContext) -> SadApplicationResult<Value> {
+    let mut entry = json!({
+        "signature": context.signature().map(|s| s.to_string()),
+        "program_key": context.program_id().to_string(),
+        "event": context.event_name(),
+        "data": context.to_json()?,
+    });
+    if let Some(slot) = context.context_slot() {
+        entry["context_slot"] = json!(slot);
+    }
+    Ok(entry)
+}
+
+/// JSON entries of the requested accounts, the missing accounts and
+/// the events of a result. Account entries carry the context slot
+/// they were read at
+fn result_json(result: &DeserializationResult, metadata: bool) -> SadApplicationResult<Vec<Value>> {
+    let mut entries = result
+        .context_vec()
+        .iter()
+        .map(|c| account_json(result, c, metadata, &mut Vec::<Pubkey>::new()))
+        .chain(
+            result
+                .missing_vec()
+                .iter()
+                .map(|k| Ok(missing_json(result, k))),
+        )
+        .collect::<SadApplicationResult<Vec<Value>>>()?;
+    if let Some(slot) = result.context_slot() {
+        for entry in entries.iter_mut() {
+            entry
+                .as_object_mut()
+                .unwrap()
+                .insert("context_slot".to_string(), json!(slot));
+        }
+    }
+    for event in result.event_vec() {
+        entries.push(event_json(event)?);
+    }
+    Ok(entries)
+}
+
+/// JSON Schema of an object with the given properties, all required
+/// but the optional ones
+fn object_json_schema(properties: Value, optional: &[&str]) -> Value {
+    let required = properties
+        .as_object()
+        .unwrap()
+        .keys()
+        .filter(|k| !optional.contains(&k.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// JSON Schema of the entries '-o json' writes for accounts of a
+/// declaration, with their metadata or references when selected
+pub fn result_json_schema(declaration: &Deseriaizer, metadata: bool, followed: bool) -> Value {
+    let mut data = declaration.json_schema();
+    let data_raw = data.as_object_mut().unwrap();
+    let schema = data_raw.remove("$schema").unwrap();
+    let title = data_raw.remove("title").unwrap();
+    let definitions = data_raw.remove("$defs");
+    let pubkey = json!({"type": "string", "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$"});
+    let unsigned = json!({"type": "integer", "minimum": 0});
+    let owner = json!({"anyOf": [pubkey, {"type": "null"}]});
+
+    let mut account = json!({
+        "account_key": pubkey,
+        "account_program_key": pubkey,
+        "data": data,
+        "context_slot": unsigned,
+    });
+    if metadata {
+        let or_null = |schema: &Value| json!({"anyOf": [schema, {"type": "null"}]});
+        account["account"] = object_json_schema(
+            json!({
+                "lamports": unsigned,
+                "sol": {"type": "number"},
+                "data_len": or_null(&unsigned),
+                "rent_exempt_minimum": or_null(&unsigned),
+                "rent_exempt": or_null(&json!({"type": "boolean"})),
+                "executable": {"type": "boolean"},
+                "rent_epoch": unsigned,
+                "data_sha256": or_null(&json!({"type": "string", "pattern": "^[0-9a-f]{64}$"})),
+            }),
+            &[],
+        );
+    }
+    if followed {
+        // Referenced accounts may be of any declaration, or unresolved
+        account["references"] = json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "account_key": pubkey,
+                    "account_program_key": owner,
+                    "field": {"type": "string"},
+                },
+                "required": ["account_key", "account_program_key", "field"],
+            },
+        });
+    }
+    let missing = object_json_schema(
+        json!({
+            "account_key": pubkey,
+            "account_program_key": owner,
+            "error": {"enum": [NO_DECLARATION_MATCHES, ACCOUNT_NOT_FOUND, DESERIALIZATION_FAILED]},
+            "detail": {"type": "string"},
+            "context_slot": unsigned,
+        }),
+        &["detail", "context_slot"],
+    );
+    let mut entries = json!({
+        "$schema": schema,
+        "title": title,
+        "type": "array",
+        "items": {
+            "oneOf": [
+                object_json_schema(account, &["context_slot", "references"]),
+                missing,
+            ]
+        },
+    });
+    if let Some(definitions) = definitions {
+        entries["$defs"] = definitions;
+    }
+    entries
+}
+
 /// Pretty prints output to sysout
 #[derive(Debug)]
 pub struct SadSysOutput {
-    deser: Deseriaizer,
     dresult: DeserializationResult,
+    metadata: bool,
 }
 
 impl SadSysOutput {
-    pub fn new(data: DeserializationResult, ddecl: Deseriaizer) -> Self {
+    pub fn new(data: DeserializationResult) -> Self {
         Self {
-            deser: ddecl,
             dresult: data,
+            metadata: false,
         }
     }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl SadOutput for SadSysOutput {
     fn write(&self) -> SadApplicationResult<()> {
-        let mut json_vector = json!([]);
-        for blocks in self.deserialization_result().context_vec() {
-            // println!(
-            //     "{}",
-            //     to_string_pretty(&self.deser.to_json(blocks.deserialize_list())).unwrap()
-            // );
-            let mut jmap = json!({});
-            let jmap_raw = jmap.as_object_mut().unwrap();
-            jmap_raw.insert(
-                "account_key".to_string(),
-                json!(blocks.pubkey().to_string()),
-            );
-            jmap_raw.insert(
-                "account_program_key".to_string(),
-                json!(blocks.account().owner.to_string()),
-            );
-            jmap_raw.insert(
-                "data".to_string(),
-                self.deser.to_json(blocks.deserialize_list()),
-            );
-            json_vector.as_array_mut().unwrap().push(jmap);
-        }
+        let json_vector = json!(result_json(self.deserialization_result(), self.metadata)?);
         println!("{}", to_string_pretty(&json_vector).unwrap());
 
         Ok(())
@@ -64,49 +330,1468 @@ impl SadOutput for SadSysOutput {
     }
 }
 
-/// Writes output to CSV file
+/// A deserialized value with its name and declared type, for display
+#[derive(Debug, PartialEq)]
+pub struct SadTypedValue {
+    pub name: String,
+    pub type_name: String,
+    /// JSON of a scalar, None for compound values
+    pub value: Option<Value>,
+    pub children: Vec<SadTypedValue>,
+}
+
+impl SadTypedValue {
+    fn compound(name: String, type_name: String, children: Vec<SadTypedValue>) -> Self {
+        Self {
+            name,
+            type_name,
+            value: None,
+            children,
+        }
+    }
+
+    /// Typed value of JSON without a declared type, its children
+    /// without a type
+    pub fn from_json(name: String, type_name: &str, value: &Value) -> Self {
+        let children = match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| SadTypedValue::from_json(k.clone(), "", v))
+                .collect(),
+            Value::Array(list) => list
+                .iter()
+                .enumerate()
+                .map(|(i, v)| SadTypedValue::from_json(i.to_string(), "", v))
+                .collect(),
+            _ => {
+                return Self {
+                    name,
+                    type_name: type_name.to_string(),
+                    value: Some(value.clone()),
+                    children: Vec::new(),
+                }
+            }
+        };
+        SadTypedValue::compound(name, type_name.to_string(), children)
+    }
+}
+
+/// Typed value of an element's JSON. References are typed with the
+/// definition name and collections with their length
+fn typed_value(
+    schema: &SadSchema,
+    element: &SadSchemaElement,
+    name: String,
+    value: &Value,
+) -> SadTypedValue {
+    let items = element.get_items();
+    let type_name = element.get_type();
+    match (type_name.as_str(), value) {
+        ("Ref", _) => {
+            let ref_name = element.get_ancillary_type().unwrap();
+            let mut typed = typed_value(schema, &schema.get_types()[ref_name], name, value);
+            typed.type_name = ref_name.clone();
+            typed
+        }
+        ("NamedField", _) => typed_value(schema, &items[0], name, value),
+        ("Option", Value::Null) => SadTypedValue::from_json(name, "Option", value),
+        ("Option", _) => typed_value(schema, &items[0], name, value),
+        ("Vec" | "Array", Value::Array(elements)) => SadTypedValue::compound(
+            name,
+            format!("{}[{}]", type_name, elements.len()),
+            elements
+                .iter()
+                .enumerate()
+                .map(|(i, e)| typed_value(schema, &items[0], i.to_string(), e))
+                .collect(),
+        ),
+        ("Tuple", Value::Array(elements)) => SadTypedValue::compound(
+            name,
+            type_name.clone(),
+            items
+                .iter()
+                .zip(elements)
+                .enumerate()
+                .map(|(i, (item, e))| typed_value(schema, item, i.to_string(), e))
+                .collect(),
+        ),
+        ("CStruct", Value::Object(fields)) => SadTypedValue::compound(
+            name,
+            type_name.clone(),
+            items
+                .iter()
+                .filter_map(|item| {
+                    let field_name = item.get_ancillary_type().unwrap();
+                    fields
+                        .get(field_name)
+                        .map(|f| typed_value(schema, item, field_name.clone(), f))
+                })
+                .collect(),
+        ),
+        ("HashMap", Value::Object(entries)) => SadTypedValue::compound(
+            name,
+            format!("{}[{}]", type_name, entries.len()),
+            entries
+                .iter()
+                .map(|(k, e)| typed_value(schema, &items[1], k.clone(), e))
+                .collect(),
+        ),
+        _ => SadTypedValue::from_json(name, type_name, value),
+    }
+}
+
+/// Typed values of deserialized items in JSON, in declaration order
+pub fn typed_values(schema: &SadSchema, json_items: &Value) -> Vec<SadTypedValue> {
+    schema
+        .get_items()
+        .iter()
+        .filter_map(|item| {
+            json_items
+                .get(item.get_name())
+                .map(|v| typed_value(schema, &item.get_items()[0], item.get_name().clone(), v))
+        })
+        .collect()
+}
+
+/// A line of the tree printed for an account or event
+struct TreeLine {
+    depth: usize,
+    name: String,
+    type_name: String,
+    value: Option<Value>,
+}
+
+/// Adds the lines of a typed value and its children
+fn push_tree_lines(typed: &SadTypedValue, depth: usize, lines: &mut Vec<TreeLine>) {
+    lines.push(TreeLine {
+        depth,
+        name: typed.name.clone(),
+        type_name: typed.type_name.clone(),
+        value: typed.value.clone(),
+    });
+    for child in &typed.children {
+        push_tree_lines(child, depth + 1, lines);
+    }
+}
+
+/// Text truncated to a width in characters, marked with an ellipsis
+fn truncate_text(text: String, width: usize) -> String {
+    if text.chars().count() <= width {
+        text
+    } else {
+        let mut truncated = text
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// Prints accounts and events to sysout as a tree of item names, types
+/// and values, aligned by account. Colored when sysout is a terminal
+/// and NO_COLOR is not set, with values truncated to the terminal width
+#[derive(Debug)]
+pub struct SadTreeOutput {
+    dresult: DeserializationResult,
+    metadata: bool,
+    color: bool,
+    width: Option<usize>,
+}
+
+impl SadTreeOutput {
+    pub fn new(data: DeserializationResult) -> Self {
+        let terminal = io::stdout().is_terminal();
+        Self {
+            dresult: data,
+            metadata: false,
+            color: terminal && std::env::var_os("NO_COLOR").is_none(),
+            width: terminal
+                .then(|| crossterm::terminal::size().ok())
+                .flatten()
+                .map(|(columns, _)| columns as usize),
+        }
+    }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Colors the output, when sysout is a terminal
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color &= color;
+        self
+    }
+
+    fn paint(&self, text: String, color: Color) -> String {
+        if self.color {
+            text.with(color).to_string()
+        } else {
+            text
+        }
+    }
+
+    fn paint_bold(&self, text: String) -> String {
+        if self.color {
+            text.bold().to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Lines of an account's items, metadata and followed references
+    fn account_lines(
+        &self,
+        context: &AccountResultContext,
+        depth: usize,
+        visiting: &mut Vec<Pubkey>,
+        lines: &mut Vec<TreeLine>,
+    ) -> SadApplicationResult<()> {
+        let declaration = context.declaration();
+        for typed in typed_values(declaration.schema(), &context.to_json()?) {
+            push_tree_lines(&typed, depth, lines);
+        }
+        if self.metadata {
+            let metadata =
+                SadTypedValue::from_json("account".to_string(), "", &metadata_json(context));
+            push_tree_lines(&metadata, depth, lines);
+        }
+        let result = self.deserialization_result();
+        if !result.is_followed() {
+            return Ok(());
+        }
+        visiting.push(*context.pubkey());
+        for reference in result
+            .references()
+            .iter()
+            .filter(|r| r.from() == context.pubkey())
+        {
+            let linked = result.context_for(reference.to());
+            lines.push(TreeLine {
+                depth,
+                name: reference.field().clone(),
+                type_name: linked.map_or("Unresolved".to_string(), |c| {
+                    c.declaration().tree().get_name().clone()
+                }),
+                value: Some(json!(reference.to().to_string())),
+            });
+            match linked {
+                Some(c) if !visiting.contains(reference.to()) => {
+                    self.account_lines(c, depth + 1, visiting, lines)?
+                }
+                _ => {}
+            }
+        }
+        visiting.pop();
+        Ok(())
+    }
+
+    /// Aligned text of the lines under a heading
+    fn format_lines(&self, lines: &[TreeLine]) -> Vec<String> {
+        let name_width = lines
+            .iter()
+            .map(|l| 2 + l.depth * 2 + l.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let type_width = lines
+            .iter()
+            .map(|l| l.type_name.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines
+            .iter()
+            .map(|line| {
+                let name = format!(
+                    "{:indent$}{:<width$}",
+                    "",
+                    line.name,
+                    indent = 2 + line.depth * 2,
+                    width = name_width - 2 - line.depth * 2
+                );
+                let type_name = format!("{:<width$}", line.type_name, width = type_width);
+                let value = match &line.value {
+                    Some(value) => {
+                        let (text, color) = match value {
+                            Value::String(text) => (text.clone(), Color::Green),
+                            Value::Bool(_) => (value.to_string(), Color::Magenta),
+                            Value::Null => (value.to_string(), Color::DarkGrey),
+                            _ => (value.to_string(), Color::Yellow),
+                        };
+                        let text = match self.width {
+                            Some(width) => truncate_text(
+                                text,
+                                width.saturating_sub(name_width + type_width + 4).max(8),
+                            ),
+                            None => text,
+                        };
+                        self.paint(text, color)
+                    }
+                    None => String::new(),
+                };
+                format!(
+                    "{}  {}  {}",
+                    self.paint(name, Color::Blue),
+                    self.paint(type_name, Color::DarkGrey),
+                    value
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+
+    /// Text of the accounts, missing accounts and events
+    fn render(&self) -> SadApplicationResult<String> {
+        let result = self.deserialization_result();
+        let slot = result
+            .context_slot()
+            .map_or(String::new(), |s| format!(" slot {}", s));
+        let mut blocks = Vec::<Vec<String>>::new();
+        for context in result.context_vec() {
+            let mut lines = Vec::<TreeLine>::new();
+            self.account_lines(context, 0, &mut Vec::new(), &mut lines)?;
+            let mut block = vec![format!(
+                "{} {}{}",
+                self.paint_bold(context.pubkey().to_string()),
+                self.paint(context.declaration().tree().get_name().clone(), Color::Cyan),
+                self.paint(
+                    format!(" owner {}{}", context.account().owner, slot),
+                    Color::DarkGrey
+                )
+            )];
+            block.extend(self.format_lines(&lines));
+            blocks.push(block);
+        }
+        for key in result.missing_vec() {
+            blocks.push(vec![format!(
+                "{} {}",
+                self.paint_bold(key.to_string()),
+                self.paint(
+                    match result.failed().get(key) {
+                        Some(detail) => format!("{}: {}", missing_reason(result, key), detail),
+                        None => missing_reason(result, key).to_string(),
+                    },
+                    Color::Red
+                )
+            )]);
+        }
+        for event in result.event_vec() {
+            let mut lines = Vec::<TreeLine>::new();
+            for typed in typed_values(event.declaration().schema(), &event.to_json()?) {
+                push_tree_lines(&typed, 0, &mut lines);
+            }
+            let signature = event
+                .signature()
+                .map_or(String::new(), |s| format!(" signature {}", s));
+            let mut block = vec![format!(
+                "{}{}",
+                self.paint(event.event_name().clone(), Color::Cyan),
+                self.paint(
+                    format!(" program {}{}", event.program_id(), signature),
+                    Color::DarkGrey
+                )
+            )];
+            block.extend(self.format_lines(&lines));
+            blocks.push(block);
+        }
+        Ok(blocks
+            .iter()
+            .map(|b| b.join("\n"))
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }
+}
+
+impl SadOutput for SadTreeOutput {
+    fn write(&self) -> SadApplicationResult<()> {
+        let text = self.render()?;
+        if !text.is_empty() {
+            println!("{}", text);
+        }
+        Ok(())
+    }
+
+    fn deserialization_result(&self) -> &DeserializationResult {
+        &self.dresult
+    }
+}
+
+/// What a file writer does with an existing output file
+#[derive(Debug, Default, PartialEq)]
+pub enum OutputFileMode {
+    /// Fail when the file exists
+    #[default]
+    Create,
+    /// Add to the accounts in the file
+    Append,
+    /// Replace the file
+    Overwrite,
+}
+
+/// Writes text to sysout, or to a file as the mode directs where
+/// appending adds the text to the end of the file
+fn write_text(
+    file_name: Option<&str>,
+    mode: &OutputFileMode,
+    text: &str,
+) -> SadApplicationResult<()> {
+    let file_name = match file_name {
+        Some(file_name) => file_name,
+        None => {
+            print!("{}", text);
+            return Ok(());
+        }
+    };
+    let mut file = match mode {
+        OutputFileMode::Create if Path::new(file_name).exists() => {
+            return Err(SadAppErrorType::OutputFileExists(file_name.to_string()))
+        }
+        OutputFileMode::Append => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file_name)?,
+        _ => File::create(file_name)?,
+    };
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// YAML of a JSON value. Integers beyond I64 are strings, as YAML
+/// integers are read as I64 and U128 are strings
+fn json_to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None if n.is_u64() => Yaml::String(n.to_string()),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(a) => Yaml::Array(a.iter().map(json_to_yaml).collect()),
+        Value::Object(o) => Yaml::Hash(
+            o.iter()
+                .map(|(k, v)| (Yaml::String(k.clone()), json_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// TOML of a JSON value, None for null which TOML lacks. Integers
+/// beyond I64, which TOML lacks, are strings as U128 are. Null array
+/// elements are empty tables, keeping the position of those after them
+fn json_to_toml(value: &Value) -> Option<toml::Value> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(toml::Value::Boolean(*b)),
+        Value::Number(n) => Some(match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None if n.is_u64() => toml::Value::String(n.to_string()),
+            None => toml::Value::Float(n.as_f64().unwrap()),
+        }),
+        Value::String(s) => Some(toml::Value::String(s.clone())),
+        Value::Array(a) => Some(toml::Value::Array(
+            a.iter()
+                .map(|v| json_to_toml(v).unwrap_or_else(|| toml::Value::Table(toml::Table::new())))
+                .collect(),
+        )),
+        Value::Object(o) => Some(toml::Value::Table(
+            o.iter()
+                .filter_map(|(k, v)| json_to_toml(v).map(|v| (k.clone(), v)))
+                .collect(),
+        )),
+    }
+}
+
+/// Position and value of the last non whitespace byte of a file
+/// before `end`
+fn last_non_whitespace(file: &mut File, mut end: u64) -> io::Result<Option<(u64, u8)>> {
+    let mut byte = [0u8; 1];
+    while end > 0 {
+        end -= 1;
+        file.seek(SeekFrom::Start(end))?;
+        file.read_exact(&mut byte)?;
+        if !byte[0].is_ascii_whitespace() {
+            return Ok(Some((end, byte[0])));
+        }
+    }
+    Ok(None)
+}
+
+/// Adds entries to the JSON array in a file, writing over its closing
+/// bracket rather than reading and rewriting the array
+fn append_json_array(file_name: &str, entries: &[Value]) -> SadApplicationResult<()> {
+    let not_array = || SadAppErrorType::ExpectedJsonArrayFile(file_name.to_string());
+    let mut file = OpenOptions::new().read(true).write(true).open(file_name)?;
+    let mut head = Vec::<u8>::new();
+    (&mut file).take(64).read_to_end(&mut head)?;
+    if head.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'[') {
+        return Err(not_array());
+    }
+    let len = file.metadata()?.len();
+    let last = match last_non_whitespace(&mut file, len)? {
+        Some((close, b']')) => last_non_whitespace(&mut file, close)?,
+        _ => None,
+    };
+    let (position, byte) = last.ok_or_else(not_array)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    // Entries as they are indented in a pretty printed array
+    let pretty = to_string_pretty(&entries).unwrap();
+    let separator = if byte == b'[' { "" } else { "," };
+    file.set_len(position + 1)?;
+    file.seek(SeekFrom::End(0))?;
+    file.write_all(format!("{}{}]", separator, &pretty[1..pretty.len() - 1]).as_bytes())?;
+    Ok(())
+}
+
+/// Writes output as a YAML document to sysout or a file. Appending
+/// adds a document to the file
+#[derive(Debug)]
+pub struct SadYamlOutput {
+    dresult: DeserializationResult,
+    file_name: Option<String>,
+    metadata: bool,
+    mode: OutputFileMode,
+}
+
+impl SadYamlOutput {
+    pub fn new(data: DeserializationResult, out_file: Option<&str>) -> Self {
+        Self {
+            dresult: data,
+            file_name: out_file.map(String::from),
+            metadata: false,
+            mode: OutputFileMode::default(),
+        }
+    }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets what is done with an existing file
+    pub fn with_mode(mut self, mode: OutputFileMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn to_yaml_string(&self) -> SadApplicationResult<String> {
+        let entries = result_json(self.deserialization_result(), self.metadata)?;
+        let mut out = String::new();
+        YamlEmitter::new(&mut out)
+            .dump(&json_to_yaml(&json!(entries)))
+            .unwrap();
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+impl SadOutput for SadYamlOutput {
+    fn write(&self) -> SadApplicationResult<()> {
+        write_text(
+            self.file_name.as_deref(),
+            &self.mode,
+            &self.to_yaml_string()?,
+        )
+    }
+
+    fn deserialization_result(&self) -> &DeserializationResult {
+        &self.dresult
+    }
+}
+
+/// Writes output as TOML to sysout or a file, the accounts as an
+/// 'accounts' array of tables or the events as an 'events' one.
+/// Appending adds the tables to the arrays in the file
+#[derive(Debug)]
+pub struct SadTomlOutput {
+    dresult: DeserializationResult,
+    file_name: Option<String>,
+    metadata: bool,
+    mode: OutputFileMode,
+}
+
+impl SadTomlOutput {
+    pub fn new(data: DeserializationResult, out_file: Option<&str>) -> Self {
+        Self {
+            dresult: data,
+            file_name: out_file.map(String::from),
+            metadata: false,
+            mode: OutputFileMode::default(),
+        }
+    }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets what is done with an existing file
+    pub fn with_mode(mut self, mode: OutputFileMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn to_toml_string(&self) -> SadApplicationResult<String> {
+        let dresult = self.deserialization_result();
+        let array_name = match dresult.account_type() {
+            ResultForKeyType::Events => "events",
+            _ => "accounts",
+        };
+        let document = json!({ array_name: result_json(dresult, self.metadata)? });
+        Ok(toml::to_string_pretty(&json_to_toml(&document).unwrap()).unwrap())
+    }
+}
+
+impl SadOutput for SadTomlOutput {
+    fn write(&self) -> SadApplicationResult<()> {
+        write_text(
+            self.file_name.as_deref(),
+            &self.mode,
+            &self.to_toml_string()?,
+        )
+    }
+
+    fn deserialization_result(&self) -> &DeserializationResult {
+        &self.dresult
+    }
+}
+
+/// Writes output to a JSON file
 #[derive(Debug)]
 pub struct SadJsonOutput {
     dresult: DeserializationResult,
-    deser: Deseriaizer,
     file_name: String,
+    metadata: bool,
+    mode: OutputFileMode,
 }
 
 impl SadJsonOutput {
-    pub fn new(data: DeserializationResult, decl: Deseriaizer, out_file: &str) -> Self {
+    pub fn new(data: DeserializationResult, out_file: &str) -> Self {
         Self {
             dresult: data,
-            deser: decl,
             file_name: out_file.to_string(),
+            metadata: false,
+            mode: OutputFileMode::default(),
         }
     }
+
+    /// Sets what is done with an existing file
+    pub fn with_mode(mut self, mode: OutputFileMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl SadOutput for SadJsonOutput {
     fn write(&self) -> SadApplicationResult<()> {
-        let fpath = std::path::Path::new(&self.file_name);
-        let mut json_vector = if fpath.exists() {
-            let data = read_to_string(fpath).expect("Unable to read file");
-            from_str(&data).expect("Unable to parse")
-        } else {
-            json!([])
+        let fpath = Path::new(&self.file_name);
+        let entries = result_json(self.deserialization_result(), self.metadata)?;
+        match self.mode {
+            OutputFileMode::Append if fpath.exists() => {
+                append_json_array(&self.file_name, &entries)
+            }
+            OutputFileMode::Create if fpath.exists() => {
+                Err(SadAppErrorType::OutputFileExists(self.file_name.clone()))
+            }
+            _ => {
+                let mut file = File::create(fpath)?;
+                file.write_all(to_string_pretty(&entries).unwrap().as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+
+    fn deserialization_result(&self) -> &DeserializationResult {
+        &self.dresult
+    }
+}
+
+/// Writes output as newline delimited JSON, one line per account or
+/// event, to sysout or appended to a file. Accounts may be written as
+/// they are deserialized
+pub struct SadNdjsonOutput {
+    writer: Box<dyn Write>,
+    metadata: bool,
+}
+
+impl SadNdjsonOutput {
+    /// Writes to the file, created, overwritten or appended to per
+    /// `mode`, or to stdout
+    pub fn new(out_file: Option<&str>, mode: OutputFileMode) -> SadApplicationResult<Self> {
+        let writer: Box<dyn Write> = match out_file {
+            Some(file_name) => Box::new(match mode {
+                OutputFileMode::Create if Path::new(file_name).exists() => {
+                    return Err(SadAppErrorType::OutputFileExists(file_name.to_string()))
+                }
+                OutputFileMode::Append => OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(file_name)?,
+                _ => File::create(file_name)?,
+            }),
+            None => Box::new(io::stdout()),
         };
-        for c in self.deserialization_result().context_vec() {
-            let mut jmap = json!({});
-            let jmap_raw = jmap.as_object_mut().unwrap();
-            jmap_raw.insert("account_key".to_string(), json!(c.pubkey().to_string()));
-            jmap_raw.insert(
-                "account_program_key".to_string(),
-                json!(c.account().owner.to_string()),
-            );
-            jmap_raw.insert("data".to_string(), self.deser.to_json(c.deserialize_list()));
-            json_vector.as_array_mut().unwrap().push(jmap);
-        }
-        let ppjson = to_string_pretty(&json_vector).unwrap();
-        let mut file = File::create(fpath).unwrap();
-        // let mut writer = BufWriter::new(file);
-        // serde_json::to_writer(&mut writer, &json_vector).unwrap();
-        file.write(ppjson.as_bytes()).unwrap();
+        Ok(Self {
+            writer,
+            metadata: false,
+        })
+    }
+
+    /// Includes the metadata of each account
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    fn write_line(&mut self, entry: &Value) -> SadApplicationResult<()> {
+        writeln!(self.writer, "{}", to_string(entry).unwrap())?;
+        Ok(())
+    }
+}
+
+impl SadStreamOutput for SadNdjsonOutput {
+    fn write_context(
+        &mut self,
+        context: &AccountResultContext,
+        slot: Slot,
+    ) -> SadApplicationResult<()> {
+        let mut entry = context_json(context, self.metadata)?;
+        entry
+            .as_object_mut()
+            .unwrap()
+            .insert("context_slot".to_string(), json!(slot));
+        self.write_line(&entry)
+    }
+
+    /// Writes the accounts, missing accounts and events a result holds
+    fn write_result(&mut self, result: &DeserializationResult) -> SadApplicationResult<()> {
+        for entry in result_json(result, self.metadata)? {
+            self.write_line(&entry)?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A top level value of a schema flattened to a column, with the
+/// keys of the path to its value in the JSON representation
+#[derive(Debug, PartialEq, Clone)]
+pub struct SadColumn {
+    pub name: String,
+    pub path: Vec<String>,
+    pub kind: SadColumnKind,
+}
+
+/// What a flattened column holds
+#[derive(Debug, PartialEq, Clone)]
+pub enum SadColumnKind {
+    /// Scalar of the type name, null for an Option that is None
+    Scalar(String),
+    /// Compound value, as JSON
+    Compound,
+    /// Vec or Array elements, with their type name if scalar
+    List(Option<String>),
+    /// HashMap entries, with the type name of the values if scalar
+    Map(Option<String>),
+}
+
+/// Type name of an element if it is scalar
+fn scalar_type_of(schema: &SadSchema, element: &SadSchemaElement) -> Option<String> {
+    let element = schema.resolve(element);
+    element.is_scalar().then(|| element.get_type().clone())
+}
+
+fn columns_for(
+    schema: &SadSchema,
+    element: &SadSchemaElement,
+    path: Vec<String>,
+    columns: &mut Vec<SadColumn>,
+) {
+    let element = schema.resolve(element);
+    let items = element.get_items();
+    let kind = match element.get_type().as_str() {
+        "CStruct" => {
+            for field in items {
+                let mut field_path = path.clone();
+                field_path.push(field.get_ancillary_type().unwrap().clone());
+                columns_for(schema, field, field_path, columns);
+            }
+            return;
+        }
+        "NamedField" => return columns_for(schema, &items[0], path, columns),
+        "Vec" | "Array" => SadColumnKind::List(scalar_type_of(schema, &items[0])),
+        "HashMap" => SadColumnKind::Map(scalar_type_of(schema, &items[1])),
+        "Option" => match scalar_type_of(schema, &items[0]) {
+            Some(scalar_type) => SadColumnKind::Scalar(scalar_type),
+            None => SadColumnKind::Compound,
+        },
+        _ if element.is_scalar() => SadColumnKind::Scalar(element.get_type().clone()),
+        _ => SadColumnKind::Compound,
+    };
+    columns.push(SadColumn {
+        name: path.join("_"),
+        path,
+        kind,
+    });
+}
+
+/// Top level items flattened to columns, with a column for each
+/// CStruct field named by its path
+pub fn schema_columns(schema: &SadSchema) -> Vec<SadColumn> {
+    let mut columns = Vec::<SadColumn>::new();
+    for item in schema.get_items() {
+        columns_for(
+            schema,
+            &item.get_items()[0],
+            vec![item.get_name().clone()],
+            &mut columns,
+        );
+    }
+    columns
+}
+
+/// SQL column type of a scalar type name. U64 columns are untyped as
+/// an INTEGER column would make values beyond an INTEGER REAL
+fn sql_type(scalar_type: &str) -> &'static str {
+    match scalar_type {
+        "U64" => "",
+        "Bool" | "U8" | "U16" | "U32" | "I8" | "I16" | "I32" | "I64" => "INTEGER",
+        "F32" | "F64" => "REAL",
+        _ => "TEXT",
+    }
+}
+
+/// Quoted SQL identifier
+fn sql_name(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQL value of JSON. Compound values are their JSON text, and U64
+/// values beyond an SQLite INTEGER are text
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None if n.is_u64() => SqlValue::Text(n.to_string()),
+            None => SqlValue::Real(n.as_f64().unwrap()),
+        },
+        Value::String(text) => SqlValue::Text(text.clone()),
+        _ => SqlValue::Text(to_string(value).unwrap()),
+    }
+}
+
+/// Upserts deserialized accounts into an SQLite database, with a table
+/// per declaration and a column per flattened top level value. Vec,
+/// Array and HashMap values are rows of a child table keyed by account.
+/// The accounts of a run are committed together
+pub struct SadSqliteOutput {
+    connection: Connection,
+    tables: HashSet<String>,
+}
+
+impl SadSqliteOutput {
+    pub fn new(file_name: &str) -> SadApplicationResult<Self> {
+        let connection = Connection::open(file_name)?;
+        connection.execute_batch("BEGIN")?;
+        Ok(Self {
+            connection,
+            tables: HashSet::new(),
+        })
+    }
+
+    /// Creates the tables of a declaration if missing
+    fn create_tables(&mut self, table: &str, columns: &[SadColumn]) -> SadApplicationResult<()> {
+        if self.tables.contains(table) {
+            return Ok(());
+        }
+        let mut definitions = vec![
+            "\"account_key\" TEXT PRIMARY KEY".to_string(),
+            "\"account_program_key\" TEXT".to_string(),
+            "\"context_slot\" INTEGER".to_string(),
+        ];
+        let mut children = Vec::<String>::new();
+        for column in columns {
+            let (key, value_type) = match &column.kind {
+                SadColumnKind::Scalar(scalar_type) => {
+                    definitions.push(
+                        format!("{} {}", sql_name(&column.name), sql_type(scalar_type))
+                            .trim_end()
+                            .to_string(),
+                    );
+                    continue;
+                }
+                SadColumnKind::Compound => {
+                    definitions.push(format!("{} TEXT", sql_name(&column.name)));
+                    continue;
+                }
+                SadColumnKind::List(value_type) => ("\"idx\" INTEGER", value_type),
+                SadColumnKind::Map(value_type) => ("\"key\" TEXT", value_type),
+            };
+            children.push(format!(
+                "CREATE TABLE IF NOT EXISTS {} (\"account_key\" TEXT NOT NULL, {} NOT NULL, \"value\" {}, PRIMARY KEY (\"account_key\", {}))",
+                sql_name(&format!("{}_{}", table, column.name)),
+                key,
+                value_type.as_deref().map_or("TEXT", sql_type),
+                key.split(' ').next().unwrap()
+            ));
+        }
+        self.connection.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} ({})",
+                sql_name(table),
+                definitions.join(", ")
+            ),
+            [],
+        )?;
+        for child in children {
+            self.connection.execute(&child, [])?;
+        }
+        self.tables.insert(table.to_string());
+        Ok(())
+    }
+}
+
+impl SadStreamOutput for SadSqliteOutput {
+    /// Upserts an account, replacing the rows of its child tables.
+    /// Values a '--fields' projection omits are left as they were
+    fn write_context(
+        &mut self,
+        context: &AccountResultContext,
+        slot: Slot,
+    ) -> SadApplicationResult<()> {
+        let table = context.declaration().tree().get_name().clone();
+        let columns = schema_columns(context.declaration().schema());
+        self.create_tables(&table, &columns)?;
+        let key = context.pubkey().to_string();
+        let data = context.to_json()?;
+        let mut names = vec![
+            sql_name("account_key"),
+            sql_name("account_program_key"),
+            sql_name("context_slot"),
+        ];
+        let mut values = vec![
+            SqlValue::Text(key.clone()),
+            SqlValue::Text(context.account().owner.to_string()),
+            SqlValue::Integer(slot as i64),
+        ];
+        for column in &columns {
+            let value = match column.path.iter().try_fold(&data, |v, k| v.get(k)) {
+                Some(value) => value,
+                None => continue,
+            };
+            match &column.kind {
+                SadColumnKind::List(_) | SadColumnKind::Map(_) => {
+                    let child = sql_name(&format!("{}_{}", table, column.name));
+                    self.connection.execute(
+                        &format!("DELETE FROM {} WHERE \"account_key\" = ?1", child),
+                        [&key],
+                    )?;
+                    let rows = match value {
+                        Value::Array(elements) => elements
+                            .iter()
+                            .enumerate()
+                            .map(|(i, e)| (SqlValue::Integer(i as i64), e))
+                            .collect(),
+                        Value::Object(entries) => entries
+                            .iter()
+                            .map(|(k, e)| (SqlValue::Text(k.clone()), e))
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    let mut insert = self
+                        .connection
+                        .prepare_cached(&format!("INSERT INTO {} VALUES (?1, ?2, ?3)", child))?;
+                    for (index, element) in rows {
+                        insert.execute(params![key, index, sql_value(element)])?;
+                    }
+                }
+                _ => {
+                    names.push(sql_name(&column.name));
+                    values.push(sql_value(value));
+                }
+            }
+        }
+        let placeholders = (1..=names.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>();
+        let updates = names[1..]
+            .iter()
+            .map(|n| format!("{} = excluded.{}", n, n))
+            .collect::<Vec<_>>();
+        self.connection.execute(
+            &format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {}",
+                sql_name(&table),
+                names.join(", "),
+                placeholders.join(", "),
+                names[0],
+                updates.join(", ")
+            ),
+            params_from_iter(values),
+        )?;
+        Ok(())
+    }
+
+    /// Upserts the accounts a result holds and commits the run
+    fn write_result(&mut self, result: &DeserializationResult) -> SadApplicationResult<()> {
+        if *result.account_type() == ResultForKeyType::Events {
+            return Err(SadAppErrorType::AccountsOnlyOutput("sqlite".to_string()));
+        }
+        let slot = result.context_slot().unwrap_or_default();
+        for context in result.context_vec().iter().chain(result.linked_vec()) {
+            self.write_context(context, slot)?;
+        }
+        self.connection.execute_batch("COMMIT")?;
+        Ok(())
+    }
+}
+
+/// Arrow type of an element's JSON as reshaped by arrow_value.
+/// References to a definition being mapped are JSON text
+fn arrow_type(
+    schema: &SadSchema,
+    element: &SadSchemaElement,
+    visiting: &mut Vec<String>,
+) -> DataType {
+    let items = element.get_items();
+    match element.get_type().as_str() {
+        "Bool" => DataType::Boolean,
+        "U8" => DataType::UInt8,
+        "U16" => DataType::UInt16,
+        "U32" => DataType::UInt32,
+        "U64" => DataType::UInt64,
+        "I8" => DataType::Int8,
+        "I16" => DataType::Int16,
+        "I32" => DataType::Int32,
+        "I64" => DataType::Int64,
+        "U128" | "I128" => DataType::Decimal256(39, 0),
+        "F32" => DataType::Float32,
+        "F64" => DataType::Float64,
+        "Ref" => {
+            let name = element.get_ancillary_type().unwrap();
+            if visiting.contains(name) {
+                return DataType::Utf8;
+            }
+            visiting.push(name.clone());
+            let data_type = arrow_type(schema, &schema.get_types()[name], visiting);
+            visiting.pop();
+            data_type
+        }
+        "Vec" | "Array" => DataType::List(Arc::new(Field::new_list_field(
+            arrow_type(schema, &items[0], visiting),
+            true,
+        ))),
+        "Option" | "NamedField" => arrow_type(schema, &items[0], visiting),
+        "Tuple" => DataType::Struct(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, e)| Field::new(i.to_string(), arrow_type(schema, e, visiting), true))
+                .collect(),
+        ),
+        "CStruct" => DataType::Struct(
+            items
+                .iter()
+                .map(|e| {
+                    Field::new(
+                        e.get_ancillary_type().unwrap(),
+                        arrow_type(schema, e, visiting),
+                        true,
+                    )
+                })
+                .collect(),
+        ),
+        "HashMap" => DataType::Map(
+            Arc::new(Field::new(
+                "entries",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("key", DataType::Utf8, false),
+                    Field::new("value", arrow_type(schema, &items[1], visiting), true),
+                ])),
+                false,
+            )),
+            false,
+        ),
+        // Strings, PublicKeys as base58, Enums and Tlvs
+        _ => DataType::Utf8,
+    }
+}
+
+/// JSON of an element reshaped for its Arrow type. Tuples become
+/// objects keyed by position, and Enums and Tlvs with fields, like
+/// references to a definition being mapped, their JSON text
+fn arrow_value(
+    schema: &SadSchema,
+    element: &SadSchemaElement,
+    value: &Value,
+    visiting: &mut Vec<String>,
+) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+    let items = element.get_items();
+    match (element.get_type().as_str(), value) {
+        ("Ref", _) => {
+            let name = element.get_ancillary_type().unwrap();
+            if visiting.contains(name) {
+                return json!(value.to_string());
+            }
+            visiting.push(name.clone());
+            let arrow_json = arrow_value(schema, &schema.get_types()[name], value, visiting);
+            visiting.pop();
+            arrow_json
+        }
+        ("Vec" | "Array", Value::Array(elements)) => Value::Array(
+            elements
+                .iter()
+                .map(|e| arrow_value(schema, &items[0], e, visiting))
+                .collect(),
+        ),
+        ("Option" | "NamedField", _) => arrow_value(schema, &items[0], value, visiting),
+        ("Tuple", Value::Array(elements)) => Value::Object(
+            items
+                .iter()
+                .zip(elements)
+                .enumerate()
+                .map(|(i, (item, e))| (i.to_string(), arrow_value(schema, item, e, visiting)))
+                .collect(),
+        ),
+        ("CStruct", Value::Object(fields)) => Value::Object(
+            items
+                .iter()
+                .filter_map(|item| {
+                    let name = item.get_ancillary_type().unwrap();
+                    fields
+                        .get(name)
+                        .map(|f| (name.clone(), arrow_value(schema, item, f, visiting)))
+                })
+                .collect(),
+        ),
+        ("HashMap", Value::Object(entries)) => Value::Object(
+            entries
+                .iter()
+                .map(|(k, e)| (k.clone(), arrow_value(schema, &items[1], e, visiting)))
+                .collect(),
+        ),
+        ("Enum" | "Tlv", Value::String(_)) => value.clone(),
+        ("Enum" | "Tlv", _) => json!(value.to_string()),
+        _ => value.clone(),
+    }
+}
+
+/// Arrow fields of the top level items
+fn arrow_fields(schema: &SadSchema) -> Vec<Field> {
+    schema
+        .get_items()
+        .iter()
+        .map(|item| {
+            Field::new(
+                item.get_name(),
+                arrow_type(schema, &item.get_items()[0], &mut Vec::new()),
+                true,
+            )
+        })
+        .collect()
+}
+
+/// JSON of deserialized items reshaped for their Arrow fields
+fn arrow_row(schema: &SadSchema, json_items: &Value) -> Value {
+    Value::Object(
+        schema
+            .get_items()
+            .iter()
+            .filter_map(|item| {
+                json_items.get(item.get_name()).map(|v| {
+                    let element = &item.get_items()[0];
+                    (
+                        item.get_name().clone(),
+                        arrow_value(schema, element, v, &mut Vec::new()),
+                    )
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Rows decoded into each Parquet record batch
+const PARQUET_BATCH_ROWS: usize = 1024;
+
+/// Writes deserialized accounts to a Parquet file, a row per account
+/// with 'account_key', 'account_program_key' and 'context_slot' columns
+/// and a column per top level item of the one declaration they share
+pub struct SadParquetOutput {
+    file_name: String,
+    mode: OutputFileMode,
+    writer: Option<(String, ArrowWriter<File>, Decoder)>,
+    rows: Vec<Value>,
+}
+
+impl SadParquetOutput {
+    /// Parquet files are written whole, so can't be appended to. The
+    /// file is created on the first write, leaving an existing file as
+    /// it is when no account is fetched
+    pub fn new(file_name: &str, mode: OutputFileMode) -> SadApplicationResult<Self> {
+        match mode {
+            OutputFileMode::Create if Path::new(file_name).exists() => {
+                return Err(SadAppErrorType::OutputFileExists(file_name.to_string()))
+            }
+            OutputFileMode::Append => {
+                return Err(SadAppErrorType::AppendUnsupported("parquet".to_string()))
+            }
+            _ => {}
+        }
+        Ok(Self {
+            file_name: file_name.to_string(),
+            mode,
+            writer: None,
+            rows: Vec::new(),
+        })
+    }
+
+    /// Arrow schema of the account columns and the declaration's items
+    fn arrow_schema(declaration: Option<&Deseriaizer>) -> SchemaRef {
+        let mut fields = vec![
+            Field::new("account_key", DataType::Utf8, false),
+            Field::new("account_program_key", DataType::Utf8, false),
+            Field::new("context_slot", DataType::UInt64, false),
+        ];
+        if let Some(declaration) = declaration {
+            fields.extend(arrow_fields(declaration.schema()));
+        }
+        Arc::new(Schema::new(fields))
+    }
+
+    /// Creates the file and the writer for the schema of a declaration
+    fn open(&mut self, declaration: Option<&Deseriaizer>) -> SadApplicationResult<()> {
+        let file = match self.mode {
+            OutputFileMode::Create => OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&self.file_name)
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::AlreadyExists => {
+                        SadAppErrorType::OutputFileExists(self.file_name.clone())
+                    }
+                    _ => e.into(),
+                })?,
+            _ => File::create(&self.file_name)?,
+        };
+        let schema = SadParquetOutput::arrow_schema(declaration);
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+        let decoder = ReaderBuilder::new(schema)
+            .with_batch_size(PARQUET_BATCH_ROWS)
+            .build_decoder()?;
+        let name = declaration.map_or(String::new(), |d| d.tree().get_name().clone());
+        self.writer = Some((name, writer, decoder));
+        Ok(())
+    }
+
+    /// Decodes the pending rows and writes them as a record batch
+    fn flush(&mut self) -> SadApplicationResult<()> {
+        let (_, writer, decoder) = self.writer.as_mut().unwrap();
+        decoder.serialize(&self.rows)?;
+        if let Some(batch) = decoder.flush()? {
+            writer.write(&batch)?;
+        }
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+impl SadStreamOutput for SadParquetOutput {
+    fn write_context(
+        &mut self,
+        context: &AccountResultContext,
+        slot: Slot,
+    ) -> SadApplicationResult<()> {
+        let declaration = context.declaration();
+        let name = declaration.tree().get_name();
+        match &self.writer {
+            None => self.open(Some(declaration))?,
+            Some((first, _, _)) if first != name => {
+                return Err(SadAppErrorType::MixedDeclarations(
+                    first.clone(),
+                    name.clone(),
+                ))
+            }
+            _ => {}
+        }
+        let mut row = arrow_row(declaration.schema(), &context.to_json()?);
+        let row_raw = row.as_object_mut().unwrap();
+        row_raw.insert(
+            "account_key".to_string(),
+            json!(context.pubkey().to_string()),
+        );
+        row_raw.insert(
+            "account_program_key".to_string(),
+            json!(context.account().owner.to_string()),
+        );
+        row_raw.insert("context_slot".to_string(), json!(slot));
+        self.rows.push(row);
+        if self.rows.len() == PARQUET_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the accounts a result holds and closes the file
+    fn write_result(&mut self, result: &DeserializationResult) -> SadApplicationResult<()> {
+        if *result.account_type() == ResultForKeyType::Events {
+            return Err(SadAppErrorType::AccountsOnlyOutput("parquet".to_string()));
+        }
+        let slot = result.context_slot().unwrap_or_default();
+        for context in result.context_vec().iter().chain(result.linked_vec()) {
+            self.write_context(context, slot)?;
+        }
+        if self.writer.is_none() {
+            self.open(None)?;
+        }
+        self.flush()?;
+        let (_, writer, _) = self.writer.take().unwrap();
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Graph representations of followed accounts
+#[derive(Debug, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+/// Prints the accounts and their references as a graph to sysout
+#[derive(Debug)]
+pub struct SadGraphOutput {
+    dresult: DeserializationResult,
+    format: GraphFormat,
+    metadata: bool,
+}
+
+impl SadGraphOutput {
+    pub fn new(data: DeserializationResult, format: GraphFormat) -> Self {
+        Self {
+            dresult: data,
+            format,
+            metadata: false,
+        }
+    }
+
+    /// Includes the metadata of each deserialized account in JSON nodes
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Every account in the graph, requested accounts first
+    fn node_keys(&self) -> Vec<Pubkey> {
+        let dresult = self.deserialization_result();
+        let mut keys = dresult
+            .context_vec()
+            .iter()
+            .chain(dresult.linked_vec().iter())
+            .map(|c| *c.pubkey())
+            .collect::<Vec<Pubkey>>();
+        for r in dresult.references() {
+            if !keys.contains(r.to()) {
+                keys.push(*r.to())
+            }
+        }
+        keys
+    }
+
+    /// Graph as a JSON object of nodes and edges
+    fn to_json(&self) -> SadApplicationResult<Value> {
+        let dresult = self.deserialization_result();
+        let nodes = self
+            .node_keys()
+            .iter()
+            .map(|k| match dresult.context_for(k) {
+                Some(c) => {
+                    let mut node = unresolved_json(dresult, k);
+                    let node_raw = node.as_object_mut().unwrap();
+                    node_raw.insert(
+                        "declaration".to_string(),
+                        json!(c.declaration().tree().get_name()),
+                    );
+                    if self.metadata {
+                        node_raw.insert("account".to_string(), metadata_json(c));
+                    }
+                    node_raw.insert("data".to_string(), c.to_json()?);
+                    Ok(node)
+                }
+                None => Ok(unresolved_json(dresult, k)),
+            })
+            .collect::<SadApplicationResult<Vec<Value>>>()?;
+        let edges = dresult
+            .references()
+            .iter()
+            .map(|r| {
+                json!({
+                    "from": r.from().to_string(),
+                    "to": r.to().to_string(),
+                    "field": r.field(),
+                })
+            })
+            .collect::<Vec<Value>>();
+        Ok(json!({ "context_slot": dresult.context_slot(), "nodes": nodes, "edges": edges }))
+    }
+
+    /// Graph in Graphviz DOT language
+    fn to_dot(&self) -> String {
+        let dresult = self.deserialization_result();
+        let mut dot = String::from("digraph sad {\n");
+        if let Some(slot) = dresult.context_slot() {
+            dot.push_str(&format!("    // context slot {}\n", slot));
+        }
+        for k in self.node_keys() {
+            match dresult.context_for(&k) {
+                Some(c) => dot.push_str(&format!(
+                    "    \"{}\" [label=\"{}\\n{}\"];\n",
+                    k,
+                    c.declaration().tree().get_name(),
+                    k
+                )),
+                None => dot.push_str(&format!("    \"{}\" [label=\"{}\", style=dashed];\n", k, k)),
+            }
+        }
+        for r in dresult.references() {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                r.from(),
+                r.to(),
+                r.field()
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+impl SadOutput for SadGraphOutput {
+    fn write(&self) -> SadApplicationResult<()> {
+        match self.format {
+            GraphFormat::Dot => println!("{}", self.to_dot()),
+            GraphFormat::Json => println!("{}", to_string_pretty(&self.to_json()?).unwrap()),
+        }
         Ok(())
     }
 
@@ -119,13 +1804,15 @@ impl SadOutput for SadJsonOutput {
 mod tests {
 
     use super::*;
-    use crate::sadtypes::{from_scalar_value_for, is_sadtype_scalar, is_simple_compound, SadValue};
     use base64::decode;
     use borsh::BorshSerialize;
     use gadgets_common::load_yaml_file;
+    use std::rc::Rc;
     use yaml_rust::Yaml;
 
-    const INDEX_STRUCT_STRING_U32: usize = 7;
+    const INDEX_STRUCT_STRING_U32: usize = 6;
+    const INDEX_RUNNER_HASHMAP_STRING_U128: usize = 2;
+    const INDEX_RUNNER_TYPEREF: usize = 10;
     #[derive(BorshSerialize)]
     struct OfStruct {
         name: String,
@@ -143,6 +1830,116 @@ mod tests {
                 .unwrap()
         }
     }
+    /// vscode changes cwd depending on running test or debugging test
+    fn get_runner_yaml() -> Vec<Yaml> {
+        if std::env::current_dir().unwrap().ends_with("sad") {
+            load_yaml_file("../yaml_samps/runner.yml").unwrap()
+        } else {
+            load_yaml_file("./yaml_samps/runner.yml").unwrap()
+        }
+    }
+
+    #[test]
+    fn test_schema_columns_pass() {
+        let desc = Deseriaizer::from_layout("spl_token_account").unwrap();
+        let columns = schema_columns(desc.schema());
+        let names = columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "mint",
+                "owner",
+                "amount",
+                "delegate_is_some",
+                "delegate_value",
+                "state",
+                "is_native_is_some",
+                "is_native_value",
+                "delegated_amount",
+                "close_authority_is_some",
+                "close_authority_value"
+            ]
+        );
+        assert_eq!(columns[2].path, vec!["amount"]);
+        assert_eq!(columns[3].path, vec!["delegate", "is_some"]);
+        assert_eq!(columns[2].kind, SadColumnKind::Scalar("U64".to_string()));
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_RUNNER_HASHMAP_STRING_U128]);
+        assert_eq!(
+            schema_columns(desc.schema())[0].kind,
+            SadColumnKind::Map(Some("U128".to_string()))
+        );
+        let desc = Deseriaizer::new(&result[INDEX_RUNNER_TYPEREF]);
+        let columns = schema_columns(desc.schema());
+        assert_eq!(columns[0].name, "owner_name");
+        assert_eq!(columns[5].name, "tree_children");
+        assert_eq!(columns[5].kind, SadColumnKind::List(None));
+    }
+
+    #[test]
+    fn test_arrow_fields_pass() {
+        let desc = Deseriaizer::from_yaml_str(
+            "types:\n  Pair:\n    type: Tuple\n    fields:\n      - type: U8\n      - type: String\narrow:\n  - big:\n      type: U128\n  - pair:\n      $ref: Pair\n  - scores:\n      type: HashMap\n      fields:\n        - type: String\n        - type: U32\n  - maybe:\n      type: Option\n      contains:\n        - type: PublicKey\n",
+        )
+        .unwrap();
+        let fields = arrow_fields(desc.schema());
+        assert_eq!(fields[0].data_type(), &DataType::Decimal256(39, 0));
+        assert_eq!(
+            fields[1].data_type(),
+            &DataType::Struct(Fields::from(vec![
+                Field::new("0", DataType::UInt8, true),
+                Field::new("1", DataType::Utf8, true)
+            ]))
+        );
+        assert!(matches!(fields[2].data_type(), DataType::Map(_, false)));
+        assert_eq!(fields[3].data_type(), &DataType::Utf8);
+        assert_eq!(
+            arrow_row(
+                desc.schema(),
+                &json!({"big": "7", "pair": [1, "a"], "scores": {"x": 2}, "maybe": null})
+            ),
+            json!({"big": "7", "pair": {"0": 1, "1": "a"}, "scores": {"x": 2}, "maybe": null})
+        );
+        // Recursive references are JSON text
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_RUNNER_TYPEREF]);
+        match arrow_fields(desc.schema())[2].data_type() {
+            DataType::Struct(fields) => match fields[1].data_type() {
+                DataType::List(item) => assert_eq!(item.data_type(), &DataType::Utf8),
+                other => panic!("Expected list, got {:?}", other),
+            },
+            other => panic!("Expected struct, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_typed_values_pass() {
+        let result = get_runner_yaml();
+        let desc = Deseriaizer::new(&result[INDEX_RUNNER_TYPEREF]);
+        let typed = typed_values(
+            desc.schema(),
+            &json!({
+                "owner": {"name": "Frank", "age": 64},
+                "tree": {"value": 1, "children": [{"value": 2, "children": []}]}
+            }),
+        );
+        assert_eq!(typed.len(), 2);
+        assert_eq!(typed[0].type_name, "Person");
+        assert_eq!(
+            typed[0].children[1],
+            SadTypedValue {
+                name: "age".to_string(),
+                type_name: "U32".to_string(),
+                value: Some(json!(64)),
+                children: vec![],
+            }
+        );
+        let children = &typed[1].children[1];
+        assert_eq!(children.type_name, "Vec[1]");
+        assert_eq!(children.children[0].type_name, "Node");
+        assert_eq!(children.children[0].children[0].value, Some(json!(2)));
+    }
+
     #[test]
     fn test_deserialization_pass() {
         let pacc = "AU8AAAADAAAABQAAAEhhcHB5CQAAAE5ldyBZZWFyIQYAAABuZXdLZXkLAAAAQSBuZXcgdmFsdWUGAAAAdHMga2V5DgAAAHRzIGZpcnN0IHZhbHVldCB2YWx1ZTIAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==";
@@ -152,77 +1949,351 @@ mod tests {
         let deserialize_vector = desc.deser(&mut pacv.as_slice()).unwrap();
         println!(
             "{}",
-            to_string_pretty(&desc.to_json(&deserialize_vector)).unwrap()
+            to_string_pretty(&desc.to_json(&deserialize_vector).unwrap()).unwrap()
         );
     }
 
-    fn write_hashmap_keyvalue(keyvalue: &Vec<SadValue>, level: &mut u32) {
-        let key = match &keyvalue[0] {
-            SadValue::String(s) => s,
-            _ => unreachable!(),
-        };
-        print!("{:indent$}{}: ", "", key, indent = *level as usize);
-        write_type(&keyvalue[1], level);
+    #[test]
+    fn test_sqlite_upsert_pass() {
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "holder:\n  - count:\n      type: U64\n  - tags:\n      type: Vec\n      contains:\n        - type: String\n",
+        )
+        .unwrap();
+        let declaration = Rc::new(Deseriaizer::new(&docs[0]));
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let file_name = std::env::temp_dir().join("sad_test_sqlite_upsert.db");
+        let _ = std::fs::remove_file(&file_name);
+        let file_name = file_name.to_str().unwrap();
+        for (count, tags) in [(5u64, vec!["a", "b"]), (u64::MAX, vec!["c"])] {
+            let mut data = count.try_to_vec().unwrap();
+            data.extend(tags.try_to_vec().unwrap());
+            let deserialized = declaration.deser(&mut data.as_slice()).unwrap();
+            let context = AccountResultContext::new(
+                key,
+                solana_sdk::account::Account::new(1, 0, &owner),
+                deserialized,
+                declaration.clone(),
+            );
+            let mut output = SadSqliteOutput::new(file_name).unwrap();
+            output.write_context(&context, 9).unwrap();
+            output
+                .write_result(&DeserializationResult::new(
+                    ResultForKeyType::MultipleAccounts,
+                    vec![],
+                ))
+                .unwrap();
+        }
+        let connection = Connection::open(file_name).unwrap();
+        let (count, slot): (String, i64) = connection
+            .query_row(
+                "SELECT count, context_slot FROM holder WHERE account_key = ?1",
+                [key.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((count, slot), (u64::MAX.to_string(), 9));
+        let tags: Vec<String> = connection
+            .prepare("SELECT value FROM holder_tags ORDER BY idx")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|t| t.unwrap())
+            .collect();
+        assert_eq!(tags, vec!["c"]);
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_rows_pass() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "holder:\n  - count:\n      type: U128\n  - tags:\n      type: Vec\n      contains:\n        - type: String\n",
+        )
+        .unwrap();
+        let declaration = Rc::new(Deseriaizer::new(&docs[0]));
+        let file_name = std::env::temp_dir().join("sad_test_parquet_rows.parquet");
+        let _ = std::fs::remove_file(&file_name);
+        let file_name = file_name.to_str().unwrap();
+        let mut contexts = Vec::<AccountResultContext>::new();
+        for (count, tags) in [(5u128, vec!["a", "b"]), (u128::MAX, vec![])] {
+            let mut data = count.try_to_vec().unwrap();
+            data.extend(tags.try_to_vec().unwrap());
+            let deserialized = declaration.deser(&mut data.as_slice()).unwrap();
+            contexts.push(AccountResultContext::new(
+                Pubkey::new_unique(),
+                solana_sdk::account::Account::new(1, 0, &Pubkey::new_unique()),
+                deserialized,
+                declaration.clone(),
+            ));
+        }
+        let result = DeserializationResult::new(ResultForKeyType::MultipleAccounts, contexts);
+        SadParquetOutput::new(file_name, OutputFileMode::Create)
+            .unwrap()
+            .write_result(&result)
+            .unwrap();
+        assert!(SadParquetOutput::new(file_name, OutputFileMode::Create).is_err());
+        // Nothing is written over until an account is
+        let written = std::fs::read(file_name).unwrap();
+        drop(SadParquetOutput::new(file_name, OutputFileMode::Overwrite).unwrap());
+        assert_eq!(std::fs::read(file_name).unwrap(), written);
+        let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(file_name).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            batch.schema().field(3).data_type(),
+            &DataType::Decimal256(39, 0)
+        );
+        let tags = batch.column(4).to_data();
+        assert_eq!(tags.buffers()[0].typed_data::<i32>(), &[0, 2, 2]);
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_tree_output_pass() {
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "holder:\n  - count:\n      type: U32\n  - label:\n      type: String\n  - tags:\n      type: Vec\n      contains:\n        - type: U8\n",
+        )
+        .unwrap();
+        let declaration = Rc::new(Deseriaizer::new(&docs[0]));
+        let mut data = 7u32.try_to_vec().unwrap();
+        data.extend("a long label to truncate".to_string().try_to_vec().unwrap());
+        data.extend(vec![1u8, 2].try_to_vec().unwrap());
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let context = AccountResultContext::new(
+            key,
+            solana_sdk::account::Account::new(1, 0, &owner),
+            declaration.deser(&mut data.as_slice()).unwrap(),
+            declaration.clone(),
+        );
+        let mut output = SadTreeOutput::new(DeserializationResult::new(
+            ResultForKeyType::SingleAccount,
+            vec![context],
+        ));
+        output.color = false;
+        let expected = format!(
+            "{} holder owner {}\n  count  U32     7\n  label  String  a long label to truncate\n  tags   Vec[2]\n    0    U8      1\n    1    U8      2",
+            key, owner
+        );
+        output.width = None;
+        assert_eq!(output.render().unwrap(), expected);
+        output.width = Some(40);
+        assert!(output
+            .render()
+            .unwrap()
+            .contains("  label  String  a long label to trunca…\n"));
+    }
+
+    #[test]
+    fn test_metadata_json_pass() {
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "meta:\n  - count:\n      type: U32\n  - name:\n      type: String\n",
+        )
+        .unwrap();
+        let data = 7u32.try_to_vec().unwrap();
+        let mut account = solana_sdk::account::Account::new(10, 0, &Pubkey::new_unique());
+        account.data = data.clone();
+        account.rent_epoch = 361;
+        let full = Rc::new(Deseriaizer::new(&docs[0]));
+        let context =
+            AccountResultContext::new(Pubkey::new_unique(), account.clone(), vec![], full);
+        let rent = Rent::default();
+        assert_eq!(
+            metadata_json(&context),
+            json!({
+                "lamports": 10,
+                "sol": 0.00000001,
+                "data_len": 4,
+                "rent_exempt_minimum": rent.minimum_balance(4),
+                "rent_exempt": false,
+                "executable": false,
+                "rent_epoch": 361,
+                "data_sha256": sha256_hex(&data),
+            })
+        );
+        let sliced = Rc::new(
+            Deseriaizer::new(&docs[0])
+                .with_fields(&["count".to_string()])
+                .unwrap(),
+        );
+        let context = AccountResultContext::new(Pubkey::new_unique(), account, vec![], sliced);
+        let json_out = metadata_json(&context);
+        assert_eq!(json_out["data_len"], Value::Null);
+        assert_eq!(json_out["data_sha256"], Value::Null);
+        assert_eq!(json_out["lamports"], json!(10));
     }
 
-    fn write_type(value: &SadValue, level: &mut u32) {
-        if is_sadtype_scalar(value) {
-            println!(
-                "{:indent$} {}",
-                "",
-                from_scalar_value_for(value),
-                indent = *level as usize
+    #[test]
+    fn test_result_json_schema_pass() {
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "holder:\n  - count:\n      type: U128\n  - owner:\n      type: PublicKey\n  - tags:\n      type: Vec\n      contains:\n        - type: String\n",
+        )
+        .unwrap();
+        let declaration = Rc::new(Deseriaizer::new(&docs[0]));
+        let mut data = u128::MAX.try_to_vec().unwrap();
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(vec!["a", "b"].try_to_vec().unwrap());
+        let context = AccountResultContext::new(
+            Pubkey::new_unique(),
+            solana_sdk::account::Account::new(1, data.len(), &Pubkey::new_unique()),
+            declaration.deser(&mut data.as_slice()).unwrap(),
+            declaration.clone(),
+        );
+        let result = DeserializationResult::new(ResultForKeyType::SingleAccount, vec![context]);
+        for metadata in [false, true] {
+            let schema = result_json_schema(&declaration, metadata, false);
+            assert_eq!(schema["title"], json!("holder"));
+            let entries = json!(result_json(&result, metadata).unwrap());
+            assert!(jsonschema::is_valid(&schema, &entries));
+            // Metadata is described only when selected
+            let other = json!(result_json(&result, !metadata).unwrap());
+            assert!(!jsonschema::is_valid(&schema, &other));
+        }
+        let schema = result_json_schema(&declaration, false, false);
+        let missing = json!([{
+            "account_key": Pubkey::new_unique().to_string(),
+            "account_program_key": null,
+            "error": ACCOUNT_NOT_FOUND,
+        }]);
+        assert!(jsonschema::is_valid(&schema, &missing));
+        let mut entries = json!(result_json(&result, false).unwrap());
+        entries[0]["data"]["count"] = json!(1);
+        assert!(!jsonschema::is_valid(&schema, &entries));
+
+        // Data that does not fit its declaration is reported with its error
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let failed = serde_json::from_value::<DeserializationResult>(json!({
+            "account_type": "MultipleAccounts",
+            "declarations": [],
+            "contexts": [],
+            "linked": [],
+            "unresolved": {key.to_string(): owner.to_string()},
+            "failed": {key.to_string(): "Expected 16 bytes of data, found 4"},
+            "missing": [key.to_string()],
+            "references": [],
+            "events": [],
+            "context_slot": null,
+        }))
+        .unwrap();
+        let entries = json!(result_json(&failed, false).unwrap());
+        assert_eq!(entries[0]["error"], json!(DESERIALIZATION_FAILED));
+        assert_eq!(
+            entries[0]["detail"],
+            json!("Expected 16 bytes of data, found 4")
+        );
+        assert!(jsonschema::is_valid(&schema, &entries));
+        let mut output = SadTreeOutput::new(failed);
+        output.color = false;
+        assert_eq!(
+            output.render().unwrap(),
+            format!(
+                "{} {}: Expected 16 bytes of data, found 4",
+                key, DESERIALIZATION_FAILED
             )
-        } else if is_simple_compound(value) {
-            match value {
-                SadValue::Vec(item) => {
-                    println!("{:indent$}Vec:", "", indent = *level as usize);
-                    *level += 1;
-                    for vi in item {
-                        write_type(vi, level)
-                    }
-                    *level -= 1
-                }
-                SadValue::Tuple(item) => {
-                    println!("{:indent$}Tuple:", "", indent = *level as usize);
-                    *level += 1;
-                    for vi in item {
-                        write_type(vi, level)
-                    }
-                    *level -= 1
-                }
-                SadValue::CStruct(item) => {
-                    println!("{:indent$}Structure {{", "", indent = *level as usize);
-                    *level += 2;
-                    for nf in item {
-                        write_type(nf, level)
-                    }
-                    *level -= 2;
-                    println!("{:indent$}}}", "", indent = *level as usize);
-                }
-                SadValue::NamedField(item) => write_hashmap_keyvalue(item, level),
-                _ => unreachable!(),
-            }
-        } else {
-            match value {
-                SadValue::HashMap(item) => {
-                    println!("{:indent$}HashMap {{", "", indent = *level as usize);
-                    for nf in item {
-                        *level += 1;
-                        write_hashmap_keyvalue(nf, level);
-                        *level -= 1;
-                        println!("{:indent$}}}", "", indent = *level as usize);
-                    }
-                }
-                _ => unreachable!(),
+        );
+    }
+
+    #[test]
+    fn test_ndjson_mode_fail() {
+        let file_name = std::env::temp_dir().join("sad_test_mode.ndjson");
+        let file_name = file_name.to_str().unwrap();
+        std::fs::write(file_name, "{}\n").unwrap();
+        assert!(matches!(
+            SadNdjsonOutput::new(Some(file_name), OutputFileMode::Create),
+            Err(SadAppErrorType::OutputFileExists(_))
+        ));
+        let mut output = SadNdjsonOutput::new(Some(file_name), OutputFileMode::Append).unwrap();
+        output.write_line(&json!({"a": 1})).unwrap();
+        drop(output);
+        assert_eq!(
+            std::fs::read_to_string(file_name).unwrap(),
+            "{}\n{\"a\":1}\n"
+        );
+        let mut output = SadNdjsonOutput::new(Some(file_name), OutputFileMode::Overwrite).unwrap();
+        output.write_line(&json!({"b": 2})).unwrap();
+        drop(output);
+        assert_eq!(std::fs::read_to_string(file_name).unwrap(), "{\"b\":2}\n");
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_append_json_array_pass() {
+        let file_name = std::env::temp_dir().join("sad_test_append.json");
+        let file_name = file_name.to_str().unwrap();
+        std::fs::write(file_name, "[]\n").unwrap();
+        append_json_array(file_name, &[json!({"a": 1})]).unwrap();
+        append_json_array(file_name, &[]).unwrap();
+        append_json_array(file_name, &[json!({"b": [2]}), json!(3)]).unwrap();
+        let appended = std::fs::read_to_string(file_name).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&appended).unwrap(),
+            json!([{"a": 1}, {"b": [2]}, 3])
+        );
+        assert_eq!(
+            appended,
+            to_string_pretty(&json!([{"a": 1}, {"b": [2]}, 3])).unwrap()
+        );
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_append_json_array_fail() {
+        let file_name = std::env::temp_dir().join("sad_test_append_fail.json");
+        let file_name = file_name.to_str().unwrap();
+        for text in ["{\"a\": [1]}", "[1, 2", "]", ""] {
+            std::fs::write(file_name, text).unwrap();
+            match append_json_array(file_name, &[json!(1)]) {
+                Err(SadAppErrorType::ExpectedJsonArrayFile(_)) => {}
+                other => panic!(
+                    "Expected not an array error for {:?}, got {:?}",
+                    text, other
+                ),
             }
         }
+        std::fs::remove_file(file_name).unwrap();
     }
-    fn write(svals: &Vec<SadValue>) {
-        let mut indent = 0;
-        for sv in svals {
-            write_type(sv, &mut indent);
-        }
+
+    #[test]
+    fn test_yaml_toml_values_pass() {
+        let value = json!({
+            "small": 7,
+            "large": u64::MAX,
+            "wide": u128::MAX.to_string(),
+            "ratio": 0.5,
+            "none": null,
+            "items": [1, null, {"key": "11111111111111111111111111111111"}],
+        });
+        let mut out = String::new();
+        YamlEmitter::new(&mut out)
+            .dump(&json_to_yaml(&value))
+            .unwrap();
+        let yaml = &yaml_rust::YamlLoader::load_from_str(&out).unwrap()[0];
+        assert_eq!(yaml["small"].as_i64(), Some(7));
+        assert_eq!(yaml["large"].as_str(), Some(u64::MAX.to_string().as_str()));
+        assert_eq!(yaml["wide"].as_str(), Some(u128::MAX.to_string().as_str()));
+        assert!(yaml["none"].is_null());
+        assert_eq!(
+            yaml["items"][2]["key"].as_str(),
+            Some("11111111111111111111111111111111")
+        );
+        let toml = json_to_toml(&value).unwrap();
+        assert_eq!(toml["small"].as_integer(), Some(7));
+        assert_eq!(toml["large"].as_str(), Some(u64::MAX.to_string().as_str()));
+        assert_eq!(toml["ratio"].as_float(), Some(0.5));
+        assert!(toml.get("none").is_none());
+        let items = toml["items"].as_array().unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1], toml::Value::Table(toml::Table::new()));
+        assert_eq!(
+            items[2]["key"].as_str(),
+            Some("11111111111111111111111111111111")
+        );
     }
 
     #[test]
@@ -231,15 +2302,16 @@ mod tests {
             name: "Frank".to_string(),
             age: 64,
         };
-        // println!("{:?}", std::env::current_dir());
-        let result = if std::env::current_dir().unwrap().ends_with("sad") {
-            load_yaml_file("../yaml_samps/runner.yml").unwrap()
-        } else {
-            load_yaml_file("./yaml_samps/runner.yml").unwrap()
-        };
-
+        let result = get_runner_yaml();
         let desc = Deseriaizer::new(&result[INDEX_STRUCT_STRING_U32]);
         let data = mhmap.try_to_vec().unwrap();
-        write(&desc.deser(&mut data.as_slice()).unwrap());
+        let json_out = desc
+            .to_json(&desc.deser(&mut data.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(json_out.as_object().unwrap().len(), 1);
+        assert_eq!(
+            json_out.as_object().unwrap().values().next().unwrap(),
+            &json!({"name": "Frank", "age": 64})
+        );
     }
 }
@@ -0,0 +1,543 @@
+//! @brief Interactive terminal browser of deserialized accounts
+//! Lists the accounts of a result beside a collapsible tree of the
+//! selected account's decoded data and a pane of its raw bytes
+
+use {
+    crate::{
+        errors::SadApplicationResult,
+        sadout::{typed_values, SadTypedValue},
+    },
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    gadgets_sad::solq::{AccountResultContext, DeserializationResult},
+    ratatui::{
+        backend::{Backend, CrosstermBackend},
+        layout::{Constraint, Direction, Layout, Rect},
+        style::{Color, Modifier, Style},
+        text::Line,
+        widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+        Frame, Terminal,
+    },
+    serde_json::Value,
+    std::{collections::HashSet, io, panic},
+};
+
+/// Bytes shown on each line of the raw bytes pane
+const BYTES_PER_LINE: usize = 16;
+/// Rows moved by page up and page down
+const PAGE_ROWS: usize = 10;
+
+/// A visible row of the tree of an account's decoded data
+#[derive(Debug, PartialEq)]
+struct TreeRow {
+    depth: usize,
+    path: String,
+    label: String,
+    expandable: bool,
+    expanded: bool,
+}
+
+/// Text of a scalar, strings without quotes
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// Label of a row, compound values with their type
+fn row_label(typed: &SadTypedValue) -> String {
+    match &typed.value {
+        Some(value) => format!("{}: {}", typed.name, scalar_text(value)),
+        None if typed.type_name.is_empty() => {
+            format!("{} ({})", typed.name, typed.children.len())
+        }
+        None => format!("{} {}", typed.name, typed.type_name),
+    }
+}
+
+/// Visible rows of decoded data, in declaration order, descending into
+/// the expanded paths
+fn tree_rows(values: &[SadTypedValue], expanded: &HashSet<String>) -> Vec<TreeRow> {
+    let mut rows = Vec::<TreeRow>::new();
+    for typed in values {
+        push_rows(typed, "", 0, expanded, &mut rows);
+    }
+    rows
+}
+
+fn push_rows(
+    typed: &SadTypedValue,
+    parent: &str,
+    depth: usize,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let path = format!("{}/{}", parent, typed.name);
+    let expandable = !typed.children.is_empty();
+    let is_expanded = expandable && expanded.contains(&path);
+    rows.push(TreeRow {
+        depth,
+        path: path.clone(),
+        label: row_label(typed),
+        expandable,
+        expanded: is_expanded,
+    });
+    if is_expanded {
+        for child in &typed.children {
+            push_rows(child, &path, depth + 1, expanded, rows);
+        }
+    }
+}
+
+/// Paths of the scalars containing the query, ignoring case, and of
+/// the compound values holding them
+fn search_paths(values: &[SadTypedValue], query: &str) -> HashSet<String> {
+    let mut paths = HashSet::<String>::new();
+    let query = query.to_lowercase();
+    for typed in values {
+        collect_matches(typed, "", &query, &mut paths);
+    }
+    paths
+}
+
+fn collect_matches(
+    typed: &SadTypedValue,
+    parent: &str,
+    query: &str,
+    paths: &mut HashSet<String>,
+) -> bool {
+    let path = format!("{}/{}", parent, typed.name);
+    let found = match &typed.value {
+        Some(value) => scalar_text(value).to_lowercase().contains(query),
+        None => {
+            let mut found = false;
+            for child in &typed.children {
+                found |= collect_matches(child, &path, query, paths);
+            }
+            found
+        }
+    };
+    if found {
+        paths.insert(path);
+    }
+    found
+}
+
+/// Hex and ASCII lines of raw bytes
+fn hex_lines(data: &[u8]) -> Vec<String> {
+    data.chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!(
+                "{:08x}  {:<width$}  |{}|",
+                i * BYTES_PER_LINE,
+                hex,
+                ascii,
+                width = BYTES_PER_LINE * 3 - 1
+            )
+        })
+        .collect()
+}
+
+/// Pane with the keyboard focus
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Pane {
+    Accounts,
+    Tree,
+    Bytes,
+}
+
+/// State of the browser over the accounts of a result
+struct Browser<'a> {
+    contexts: Vec<&'a AccountResultContext>,
+    /// Decoded data of each account, typed by its declaration up front
+    values: Vec<Vec<SadTypedValue>>,
+    /// Indexes of the accounts matching the search
+    visible: Vec<usize>,
+    accounts: ListState,
+    tree: ListState,
+    expanded: HashSet<String>,
+    matches: HashSet<String>,
+    bytes_scroll: usize,
+    focus: Pane,
+    query: String,
+    editing: bool,
+}
+
+impl<'a> Browser<'a> {
+    fn new(result: &'a DeserializationResult) -> SadApplicationResult<Self> {
+        let contexts = result
+            .context_vec()
+            .iter()
+            .chain(result.linked_vec())
+            .collect::<Vec<_>>();
+        let values = contexts
+            .iter()
+            .map(|c| Ok(typed_values(c.declaration().schema(), &c.to_json()?)))
+            .collect::<SadApplicationResult<Vec<_>>>()?;
+        let visible = (0..contexts.len()).collect::<Vec<_>>();
+        let mut accounts = ListState::default();
+        if !visible.is_empty() {
+            accounts.select(Some(0));
+        }
+        Ok(Self {
+            contexts,
+            values,
+            visible,
+            accounts,
+            tree: ListState::default().with_selected(Some(0)),
+            expanded: HashSet::new(),
+            matches: HashSet::new(),
+            bytes_scroll: 0,
+            focus: Pane::Accounts,
+            query: String::new(),
+            editing: false,
+        })
+    }
+
+    /// Index of the selected account
+    fn selected(&self) -> Option<usize> {
+        self.accounts.selected().map(|i| self.visible[i])
+    }
+
+    fn rows(&self) -> Vec<TreeRow> {
+        match self.selected() {
+            Some(index) => tree_rows(&self.values[index], &self.expanded),
+            None => Vec::new(),
+        }
+    }
+
+    /// Selects an account, expanding the paths to its search matches
+    fn select_account(&mut self, position: Option<usize>) {
+        self.accounts.select(position);
+        self.tree.select(Some(0));
+        self.bytes_scroll = 0;
+        self.matches = match self.selected() {
+            Some(index) if !self.query.is_empty() => search_paths(&self.values[index], &self.query),
+            _ => HashSet::new(),
+        };
+        self.expanded.extend(self.matches.iter().cloned());
+    }
+
+    /// Shows the accounts whose key or decoded data contains the query
+    fn apply_search(&mut self) {
+        let query = self.query.to_lowercase();
+        self.visible = (0..self.contexts.len())
+            .filter(|i| {
+                query.is_empty()
+                    || self.contexts[*i]
+                        .pubkey()
+                        .to_string()
+                        .to_lowercase()
+                        .contains(&query)
+                    || !search_paths(&self.values[*i], &query).is_empty()
+            })
+            .collect();
+        let position = (!self.visible.is_empty()).then_some(0);
+        self.select_account(position);
+    }
+
+    /// Moves the selection of the focused pane by delta rows
+    fn move_by(&mut self, delta: isize) {
+        let step = |current: usize, len: usize| {
+            (current as isize + delta).clamp(0, len.saturating_sub(1) as isize) as usize
+        };
+        match self.focus {
+            Pane::Accounts if !self.visible.is_empty() => {
+                let position = step(self.accounts.selected().unwrap_or(0), self.visible.len());
+                self.select_account(Some(position));
+            }
+            Pane::Tree => {
+                let len = self.rows().len();
+                self.tree
+                    .select(Some(step(self.tree.selected().unwrap_or(0), len)));
+            }
+            Pane::Bytes => {
+                let len = self
+                    .selected()
+                    .map_or(0, |i| hex_lines(&self.contexts[i].account().data).len());
+                self.bytes_scroll = step(self.bytes_scroll, len);
+            }
+            _ => {}
+        }
+    }
+
+    /// Expands or collapses the selected tree row
+    fn set_expanded(&mut self, expand: bool) {
+        let rows = self.rows();
+        if let Some(row) = self.tree.selected().and_then(|i| rows.get(i)) {
+            if !row.expandable {
+                return;
+            }
+            if expand {
+                self.expanded.insert(row.path.clone());
+            } else {
+                self.expanded.remove(&row.path);
+            }
+        }
+    }
+
+    /// Handles a key, returning false to quit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if self.editing {
+            match code {
+                KeyCode::Enter => {
+                    self.editing = false;
+                    self.apply_search();
+                }
+                KeyCode::Esc => self.editing = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Char(c) => self.query.push(c),
+                _ => {}
+            }
+            return true;
+        }
+        match code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Esc if self.query.is_empty() => return false,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.apply_search();
+            }
+            KeyCode::Char('/') => {
+                self.editing = true;
+                self.query.clear();
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Pane::Accounts => Pane::Tree,
+                    Pane::Tree => Pane::Bytes,
+                    Pane::Bytes => Pane::Accounts,
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-(PAGE_ROWS as isize)),
+            KeyCode::PageDown => self.move_by(PAGE_ROWS as isize),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') if self.focus == Pane::Tree => {
+                self.set_expanded(true)
+            }
+            KeyCode::Left if self.focus == Pane::Tree => self.set_expanded(false),
+            _ => {}
+        }
+        true
+    }
+
+    fn block(&self, title: String, pane: Pane) -> Block<'static> {
+        let style = if self.focus == pane {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(style)
+            .title(title)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let outer = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(frame.size());
+        let main = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(outer[0]);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(main[1]);
+        self.draw_accounts(frame, main[0]);
+        self.draw_tree(frame, right[0]);
+        self.draw_bytes(frame, right[1]);
+        let status = if self.editing {
+            format!("/{}", self.query)
+        } else if self.query.is_empty() {
+            "Up/Down move  Tab pane  Right/Left expand/collapse  / search  q quit".to_string()
+        } else {
+            format!("Search '{}'  Esc clears  q quit", self.query)
+        };
+        frame.render_widget(Paragraph::new(status), outer[1]);
+    }
+
+    fn draw_accounts(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .visible
+            .iter()
+            .map(|i| {
+                let context = self.contexts[*i];
+                ListItem::new(format!(
+                    "{} {}",
+                    context.pubkey(),
+                    context.declaration().tree().get_name()
+                ))
+            })
+            .collect::<Vec<_>>();
+        let title = format!("Accounts {}/{}", self.visible.len(), self.contexts.len());
+        let list = List::new(items)
+            .block(self.block(title, Pane::Accounts))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.accounts);
+    }
+
+    fn draw_tree(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let marker = match (row.expandable, row.expanded) {
+                    (true, true) => "- ",
+                    (true, false) => "+ ",
+                    _ => "  ",
+                };
+                let text = format!("{}{}{}", "  ".repeat(row.depth), marker, row.label);
+                if !row.expandable && self.matches.contains(&row.path) {
+                    ListItem::new(text).style(Style::default().fg(Color::Yellow))
+                } else {
+                    ListItem::new(text)
+                }
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(self.block("Data".to_string(), Pane::Tree))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.tree);
+    }
+
+    fn draw_bytes(&self, frame: &mut Frame, area: Rect) {
+        let (lines, title) = match self.selected() {
+            Some(index) => {
+                let context = self.contexts[index];
+                let data = &context.account().data;
+                let sliced = if context.is_sliced() { ", sliced" } else { "" };
+                (
+                    hex_lines(data).into_iter().map(Line::from).collect(),
+                    format!("Raw bytes {}{}", data.len(), sliced),
+                )
+            }
+            None => (Vec::new(), "Raw bytes".to_string()),
+        };
+        let paragraph = Paragraph::new(lines)
+            .block(self.block(title, Pane::Bytes))
+            .scroll((self.bytes_scroll as u16, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Browses the accounts of a result in the terminal until quit
+pub fn browse(result: &DeserializationResult) -> SadApplicationResult<()> {
+    let mut browser = Browser::new(result)?;
+    // A panic restores the terminal before its message is printed
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let outcome = browser.run(&mut terminal);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    // Back to the default hook
+    let _ = panic::take_hook();
+    Ok(outcome?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets_sad::Deseriaizer;
+    use serde_json::json;
+
+    const HOLDER_DECL: &str = "holder:\n  - owner:\n      type: String\n  - amounts:\n      type: Vec\n      contains:\n        - type: U8\n  - inner:\n      type: CStruct\n      fields:\n        - type: NamedField\n          descriptor:\n            name: flag\n            contains:\n              type: Bool\n";
+
+    #[test]
+    fn test_tree_rows_pass() {
+        let declaration = Deseriaizer::from_yaml_str(HOLDER_DECL).unwrap();
+        let value = json!({"owner": "abc", "amounts": [1, 2], "inner": {"flag": true}});
+        let values = typed_values(declaration.schema(), &value);
+        // Rows are in declaration order rather than by name
+        let rows = tree_rows(&values, &HashSet::new());
+        assert_eq!(
+            rows.iter().map(|r| r.label.as_str()).collect::<Vec<_>>(),
+            vec!["owner: abc", "amounts Vec[2]", "inner CStruct"]
+        );
+        let expanded = HashSet::from(["/amounts".to_string()]);
+        let rows = tree_rows(&values, &expanded);
+        assert_eq!(
+            rows[3],
+            TreeRow {
+                depth: 1,
+                path: "/amounts/1".to_string(),
+                label: "1: 2".to_string(),
+                expandable: false,
+                expanded: false,
+            }
+        );
+        assert!(rows[1].expanded);
+    }
+
+    #[test]
+    fn test_search_paths_pass() {
+        let value = json!({"owner": "AbC", "inner": {"names": ["x", "zabcz"]}});
+        let values = SadTypedValue::from_json(String::new(), "", &value).children;
+        let paths = search_paths(&values, "abc");
+        let expected = ["/owner", "/inner", "/inner/names", "/inner/names/1"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(paths, expected);
+        assert!(search_paths(&values, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_hex_lines_pass() {
+        let mut data = b"sad data".to_vec();
+        data.extend([0u8; 10]);
+        let lines = hex_lines(&data);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000000  73 61 64 20 64 61 74 61 00 00 00 00 00 00 00 00  |sad data........|"
+        );
+        assert!(lines[1].starts_with("00000010  00 00  "));
+        assert!(lines[1].ends_with("|..|"));
+    }
+}